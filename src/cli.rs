@@ -5,6 +5,42 @@ use structopt::StructOpt;
 pub struct Options {
     #[structopt(subcommand)]
     pub command: Option<Command>,
+
+    /// Records the session's input to a file, written on exit
+    #[structopt(long, parse(from_os_str))]
+    pub record: Option<std::path::PathBuf>,
+
+    /// Captures every frame to numbered PNGs in a directory, or raw RGBA to stdout with "-",
+    /// exiting when the capture finishes
+    #[structopt(long)]
+    pub capture: Option<crate::context::capture::CaptureOutput>,
+
+    /// The size of captured frames as WIDTHxHEIGHT
+    #[structopt(long, default_value = "1920x1080", parse(try_from_str = parse_size))]
+    pub capture_size: (u32, u32),
+
+    /// Frames per second of simulated time while capturing
+    #[structopt(long, default_value = "60")]
+    pub capture_rate: f32,
+
+    /// Stops the capture after this many frames
+    #[structopt(long)]
+    pub capture_frames: Option<u32>,
+}
+
+fn parse_size(text: &str) -> Result<(u32, u32), String> {
+    let (width, height) = text
+        .split_once('x')
+        .ok_or_else(|| format!("Expected WIDTHxHEIGHT, got {text}"))?;
+    let parse = |dimension: &str| {
+        dimension
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|dimension| *dimension > 0)
+            .ok_or_else(|| format!("Invalid size {text}"))
+    };
+    Ok((parse(width)?, parse(height)?))
 }
 
 #[derive(Default, Debug, StructOpt)]
@@ -13,4 +49,15 @@ pub enum Command {
     #[structopt(about = "Run the editor")]
     #[default]
     Run,
+
+    /// Replays an input recording made with --record
+    #[structopt(about = "Replay an input recording")]
+    Replay {
+        #[structopt(parse(from_os_str))]
+        path: std::path::PathBuf,
+
+        /// Runs the replay without a window or renderer, exiting when it ends
+        #[structopt(long)]
+        headless: bool,
+    },
 }
@@ -1,27 +1,59 @@
+pub mod actions;
 pub mod camera;
+pub mod camera_path;
+pub mod capture;
+pub mod config;
+pub mod console;
 pub mod graphics;
 pub mod input;
 pub mod paint;
+pub mod panels;
+pub mod play;
+pub mod profiler;
+pub mod replay;
+pub mod schedule;
+pub mod screen;
+pub mod time;
 pub mod transform;
 pub mod tree;
 pub mod ui;
 pub mod window;
 
 crate::ecs! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     Context {
         camera: camera::Camera => CAMERA,
+        camera_controller: camera::CameraController => CAMERA_CONTROLLER,
+        camera_path: camera_path::CameraPath => CAMERA_PATH,
         global_transform: transform::GlobalTransform => GLOBAL_TRANSFORM,
         lines: paint::Lines => LINES,
         local_transform: transform::LocalTransform => LOCAL_TRANSFORM,
         name: tree::Name => NAME,
         parent: tree::Parent => PARENT,
         quads: paint::Quads => QUADS,
+        render_transform: transform::RenderTransform => RENDER_TRANSFORM,
+        sibling_index: tree::SiblingIndex => SIBLING_INDEX,
     }
     Resources {
         window: window::Window,
         graphics: graphics::Graphics,
         user_interface: ui::UserInterface,
         input: input::Input,
+        action_map: actions::ActionMap,
         active_camera_entity: Option<EntityId>,
+        editor_config: config::EditorConfig,
+        frame_capture: capture::FrameCapture,
+        log_sink: console::LogSink,
+        orphan_policy: tree::OrphanPolicy,
+        panel_registry: panels::PanelRegistry,
+        play_session: play::PlaySession,
+        profiler: profiler::Profiler,
+        replay: replay::Replay,
+        schedule: schedule::Schedule,
+        sequencer: camera_path::Sequencer,
+        time: time::Time,
+    }
+    Events {
+        window_resized: window::WindowResized,
     }
 }
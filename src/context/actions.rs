@@ -0,0 +1,629 @@
+use crate::context::{
+    config::config_directory,
+    input::{Input, MouseState},
+    Context,
+};
+
+pub const MOVE_FORWARD: &str = "Move Forward";
+pub const MOVE_RIGHT: &str = "Move Right";
+pub const MOVE_UP: &str = "Move Up";
+pub const ROLL: &str = "Roll";
+pub const BOOST: &str = "Boost";
+pub const LOOK: &str = "Look";
+pub const PAN: &str = "Pan";
+pub const ZOOM: &str = "Zoom";
+pub const FOCUS_SELECTION: &str = "Focus Selection";
+pub const EXIT_RUN_MODE: &str = "Exit Run Mode";
+pub const PAUSE: &str = "Pause";
+pub const STEP: &str = "Step";
+pub const SLOW_DOWN: &str = "Slow Down";
+pub const SPEED_UP: &str = "Speed Up";
+
+/// A physical input that can be bound to an action
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum InputSource {
+    Key(winit::keyboard::KeyCode),
+    Mouse(winit::event::MouseButton),
+    ScrollUp,
+    ScrollDown,
+}
+
+/// Modifier keys that must be held for a binding to activate
+#[derive(
+    Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    /// Whether every required modifier is held, other modifiers are ignored
+    pub fn held(&self, state: winit::keyboard::ModifiersState) -> bool {
+        (!self.shift || state.shift_key())
+            && (!self.control || state.control_key())
+            && (!self.alt || state.alt_key())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Binding {
+    /// Active while the input and modifiers are held
+    Button {
+        source: InputSource,
+        #[serde(default)]
+        modifiers: Modifiers,
+    },
+    /// Positive while one input is held and negative while the other is
+    Axis {
+        positive: InputSource,
+        negative: InputSource,
+    },
+}
+
+impl Binding {
+    fn key(key_code: winit::keyboard::KeyCode) -> Self {
+        Self::Button {
+            source: InputSource::Key(key_code),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn keys(positive: winit::keyboard::KeyCode, negative: winit::keyboard::KeyCode) -> Self {
+        Self::Axis {
+            positive: InputSource::Key(positive),
+            negative: InputSource::Key(negative),
+        }
+    }
+}
+
+/// The part of a binding waiting for the next input in the bindings editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingCapture {
+    pub action: String,
+    /// The binding being replaced, or a new binding when past the end
+    pub index: usize,
+    pub slot: BindingSlot,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BindingSlot {
+    Button,
+    Positive,
+    Negative,
+}
+
+/// A resource mapping named actions to the inputs that trigger them
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ActionMap {
+    pub bindings: std::collections::BTreeMap<String, Vec<Binding>>,
+
+    /// The binding waiting for input in the bindings editor
+    #[serde(skip)]
+    pub capture: Option<BindingCapture>,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        use winit::keyboard::KeyCode;
+        let bindings = [
+            (
+                MOVE_FORWARD,
+                vec![Binding::keys(KeyCode::KeyW, KeyCode::KeyS)],
+            ),
+            (
+                MOVE_RIGHT,
+                vec![Binding::keys(KeyCode::KeyD, KeyCode::KeyA)],
+            ),
+            (
+                MOVE_UP,
+                vec![
+                    Binding::keys(KeyCode::Space, KeyCode::KeyQ),
+                    Binding::keys(KeyCode::KeyE, KeyCode::ControlLeft),
+                ],
+            ),
+            (ROLL, vec![Binding::keys(KeyCode::KeyZ, KeyCode::KeyC)]),
+            (BOOST, vec![Binding::key(KeyCode::ShiftLeft)]),
+            (
+                LOOK,
+                vec![Binding::Button {
+                    source: InputSource::Mouse(winit::event::MouseButton::Right),
+                    modifiers: Modifiers::default(),
+                }],
+            ),
+            (
+                PAN,
+                vec![Binding::Button {
+                    source: InputSource::Mouse(winit::event::MouseButton::Middle),
+                    modifiers: Modifiers::default(),
+                }],
+            ),
+            (
+                ZOOM,
+                vec![Binding::Axis {
+                    positive: InputSource::ScrollUp,
+                    negative: InputSource::ScrollDown,
+                }],
+            ),
+            (FOCUS_SELECTION, vec![Binding::key(KeyCode::KeyF)]),
+            (EXIT_RUN_MODE, vec![Binding::key(KeyCode::Escape)]),
+            (PAUSE, vec![Binding::key(KeyCode::KeyP)]),
+            (STEP, vec![Binding::key(KeyCode::Period)]),
+            (SLOW_DOWN, vec![Binding::key(KeyCode::BracketLeft)]),
+            (SPEED_UP, vec![Binding::key(KeyCode::BracketRight)]),
+        ]
+        .into_iter()
+        .map(|(action, bindings)| (action.to_string(), bindings))
+        .collect();
+        Self {
+            bindings,
+            capture: None,
+        }
+    }
+}
+
+/// Query for the value of an action from the held inputs, between -1 and 1 for axes.
+/// Scroll inputs report the distance scrolled this frame.
+pub fn query_action_value(context: &Context, action: &str) -> f32 {
+    let input = &context.resources.input;
+    let Some(bindings) = context.resources.action_map.bindings.get(action) else {
+        return 0.0;
+    };
+    bindings
+        .iter()
+        .map(|binding| match binding {
+            Binding::Button { source, modifiers } => {
+                if modifiers.held(input.keyboard.modifiers) {
+                    query_source_value(input, *source)
+                } else {
+                    0.0
+                }
+            }
+            Binding::Axis { positive, negative } => {
+                query_source_value(input, *positive) - query_source_value(input, *negative)
+            }
+        })
+        .fold(0.0, |value: f32, binding_value| {
+            if binding_value.abs() > value.abs() {
+                binding_value
+            } else {
+                value
+            }
+        })
+}
+
+/// Whether any input bound to an action is held
+pub fn is_action_active(context: &Context, action: &str) -> bool {
+    query_action_value(context, action) != 0.0
+}
+
+/// Whether a button bound to an action was pressed this frame
+pub fn is_action_just_pressed(context: &Context, action: &str) -> bool {
+    if context.resources.action_map.capture.is_some() {
+        return false;
+    }
+    let input = &context.resources.input;
+    context
+        .resources
+        .action_map
+        .bindings
+        .get(action)
+        .is_some_and(|bindings| {
+            bindings.iter().any(|binding| {
+                matches!(binding, Binding::Button { source, modifiers }
+                    if query_source_just_pressed(input, *source)
+                        && modifiers.held(input.keyboard.modifiers))
+            })
+        })
+}
+
+/// Whether a window event is a fresh press of a button bound to an action.
+/// Inputs pressed while the bindings editor is waiting for input trigger nothing.
+pub fn is_action_triggered(
+    context: &Context,
+    action: &str,
+    event: &winit::event::WindowEvent,
+) -> bool {
+    if context.resources.action_map.capture.is_some() {
+        return false;
+    }
+    let Some(pressed_source) = query_pressed_source(event) else {
+        return false;
+    };
+    let modifiers_state = context.resources.input.keyboard.modifiers;
+    context
+        .resources
+        .action_map
+        .bindings
+        .get(action)
+        .is_some_and(|bindings| {
+            bindings.iter().any(|binding| {
+                matches!(binding, Binding::Button { source, modifiers }
+                    if *source == pressed_source && modifiers.held(modifiers_state))
+            })
+        })
+}
+
+fn query_source_value(input: &Input, source: InputSource) -> f32 {
+    let mouse = &input.mouse;
+    match source {
+        InputSource::Key(key_code) => input.keyboard.is_key_pressed(key_code) as i32 as f32,
+        InputSource::Mouse(button) => {
+            mouse_button_flag(button).is_some_and(|flag| mouse.state.contains(flag)) as i32 as f32
+        }
+        InputSource::ScrollUp => mouse.wheel_delta.y.max(0.0),
+        InputSource::ScrollDown => (-mouse.wheel_delta.y).max(0.0),
+    }
+}
+
+/// Scrolling counts as a press on the frame it happens and never as a release
+fn query_source_just_pressed(input: &Input, source: InputSource) -> bool {
+    match source {
+        InputSource::Key(key_code) => input.keyboard.is_key_just_pressed(key_code),
+        InputSource::Mouse(button) => {
+            mouse_button_flag(button).is_some_and(|flag| input.mouse.just_pressed.contains(flag))
+        }
+        InputSource::ScrollUp | InputSource::ScrollDown => query_source_value(input, source) > 0.0,
+    }
+}
+
+pub fn mouse_button_flag(button: winit::event::MouseButton) -> Option<MouseState> {
+    match button {
+        winit::event::MouseButton::Left => Some(MouseState::LEFT_CLICKED),
+        winit::event::MouseButton::Middle => Some(MouseState::MIDDLE_CLICKED),
+        winit::event::MouseButton::Right => Some(MouseState::RIGHT_CLICKED),
+        _ => None,
+    }
+}
+
+/// The input pressed by a window event, ignoring key repeats
+fn query_pressed_source(event: &winit::event::WindowEvent) -> Option<InputSource> {
+    match event {
+        winit::event::WindowEvent::KeyboardInput {
+            event:
+                winit::event::KeyEvent {
+                    physical_key: winit::keyboard::PhysicalKey::Code(key_code),
+                    state: winit::event::ElementState::Pressed,
+                    repeat: false,
+                    ..
+                },
+            ..
+        } => Some(InputSource::Key(*key_code)),
+        winit::event::WindowEvent::MouseInput {
+            state: winit::event::ElementState::Pressed,
+            button,
+            ..
+        } => Some(InputSource::Mouse(*button)),
+        winit::event::WindowEvent::MouseWheel { delta, .. } => {
+            let vertical = match delta {
+                winit::event::MouseScrollDelta::LineDelta(_, v_lines) => *v_lines as f64,
+                winit::event::MouseScrollDelta::PixelDelta(position) => position.y,
+            };
+            if vertical > 0.0 {
+                Some(InputSource::ScrollUp)
+            } else if vertical < 0.0 {
+                Some(InputSource::ScrollDown)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Query for the inputs bound to more than one action, with the actions sharing them
+pub fn query_binding_conflicts(
+    action_map: &ActionMap,
+) -> Vec<((InputSource, Modifiers), Vec<String>)> {
+    let mut users =
+        std::collections::BTreeMap::<String, ((InputSource, Modifiers), Vec<String>)>::new();
+    for (action, bindings) in &action_map.bindings {
+        for binding in bindings {
+            let inputs = match binding {
+                Binding::Button { source, modifiers } => vec![(*source, *modifiers)],
+                Binding::Axis { positive, negative } => vec![
+                    (*positive, Modifiers::default()),
+                    (*negative, Modifiers::default()),
+                ],
+            };
+            for input in inputs {
+                let (_, actions) = users
+                    .entry(format!("{input:?}"))
+                    .or_insert_with(|| (input, Vec::new()));
+                if !actions.contains(action) {
+                    actions.push(action.clone());
+                }
+            }
+        }
+    }
+    users
+        .into_values()
+        .filter(|(_, actions)| actions.len() > 1)
+        .collect()
+}
+
+/// Completes a binding capture with the next pressed input, escape cancels it
+pub fn receive_window_event(context: &mut Context, event: &winit::event::WindowEvent) {
+    if context.resources.action_map.capture.is_none() {
+        return;
+    }
+    let Some(source) = query_pressed_source(event) else {
+        return;
+    };
+    let modifiers_state = context.resources.input.keyboard.modifiers;
+    let action_map = &mut context.resources.action_map;
+    let Some(BindingCapture {
+        action,
+        index,
+        slot,
+    }) = action_map.capture.take()
+    else {
+        return;
+    };
+    // The input has already reached the input state, and would otherwise
+    // trigger the action it was just bound to once the capture is over
+    suppress_source_press(&mut context.resources.input, source);
+    if source == InputSource::Key(winit::keyboard::KeyCode::Escape) {
+        return;
+    }
+
+    // A modifier key being bound is not also required as a modifier
+    use winit::keyboard::KeyCode;
+    let is_key =
+        |codes: &[KeyCode]| matches!(source, InputSource::Key(code) if codes.contains(&code));
+    let modifiers = Modifiers {
+        shift: modifiers_state.shift_key() && !is_key(&[KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        control: modifiers_state.control_key()
+            && !is_key(&[KeyCode::ControlLeft, KeyCode::ControlRight]),
+        alt: modifiers_state.alt_key() && !is_key(&[KeyCode::AltLeft, KeyCode::AltRight]),
+    };
+
+    let bindings = action_map.bindings.entry(action).or_default();
+    let binding = match (bindings.get(index).copied(), slot) {
+        (Some(Binding::Axis { negative, .. }), BindingSlot::Positive) => Binding::Axis {
+            positive: source,
+            negative,
+        },
+        (Some(Binding::Axis { positive, .. }), BindingSlot::Negative) => Binding::Axis {
+            positive,
+            negative: source,
+        },
+        _ => Binding::Button { source, modifiers },
+    };
+    if index < bindings.len() {
+        bindings[index] = binding;
+    } else {
+        bindings.push(binding);
+    }
+}
+
+/// Removes this frame's press of an input, so no binding sees it as just pressed
+fn suppress_source_press(input: &mut Input, source: InputSource) {
+    match source {
+        InputSource::Key(key_code) => {
+            input.keyboard.just_pressed.remove(&key_code);
+        }
+        InputSource::Mouse(button) => {
+            if let Some(flag) = mouse_button_flag(button) {
+                input.mouse.just_pressed.remove(flag);
+            }
+        }
+        InputSource::ScrollUp | InputSource::ScrollDown => {
+            input.mouse.wheel_delta.y = 0.0;
+        }
+    }
+}
+
+pub fn input_source_label(source: InputSource) -> String {
+    match source {
+        InputSource::Key(key_code) => {
+            let name = format!("{key_code:?}");
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .map(str::to_string)
+                .unwrap_or(name)
+        }
+        InputSource::Mouse(button) => format!("Mouse {button:?}"),
+        InputSource::ScrollUp => "Scroll Up".to_string(),
+        InputSource::ScrollDown => "Scroll Down".to_string(),
+    }
+}
+
+fn modifiers_label(modifiers: Modifiers) -> String {
+    [
+        (modifiers.control, "Ctrl+"),
+        (modifiers.shift, "Shift+"),
+        (modifiers.alt, "Alt+"),
+    ]
+    .into_iter()
+    .filter(|(held, _)| *held)
+    .map(|(_, label)| label)
+    .collect()
+}
+
+/// The bindings editor, listing every action with buttons to rebind its inputs
+pub fn bindings_ui(context: &mut Context, ui: &mut egui::Ui) {
+    let conflicts = query_binding_conflicts(&context.resources.action_map);
+    let action_map = &mut context.resources.action_map;
+
+    ui.horizontal(|ui| {
+        if ui.button("Save").clicked() {
+            save_action_map(action_map);
+        }
+        if ui.button("Reset to Defaults").clicked() {
+            *action_map = ActionMap::default();
+        }
+        if action_map.capture.is_some() {
+            ui.label(
+                egui::RichText::new("Press an input to bind, or Escape to cancel")
+                    .color(egui::Color32::YELLOW),
+            );
+        }
+    });
+
+    for ((source, modifiers), actions) in &conflicts {
+        ui.label(
+            egui::RichText::new(format!(
+                "⚠ {}{} is bound to {}",
+                modifiers_label(*modifiers),
+                input_source_label(*source),
+                actions.join(", ")
+            ))
+            .color(egui::Color32::from_rgb(230, 120, 80)),
+        );
+    }
+    ui.separator();
+
+    let conflicting = |source: InputSource, modifiers: Modifiers| {
+        conflicts
+            .iter()
+            .any(|((conflict_source, conflict_modifiers), _)| {
+                *conflict_source == source && *conflict_modifiers == modifiers
+            })
+    };
+    let capture = action_map.capture.clone();
+    let mut new_capture = None;
+
+    egui::Grid::new("bindings")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (action, bindings) in action_map.bindings.iter_mut() {
+                ui.label(action.as_str());
+                ui.horizontal_wrapped(|ui| {
+                    let mut removed = None;
+                    for (index, binding) in bindings.iter().enumerate() {
+                        let mut slot_button =
+                            |ui: &mut egui::Ui,
+                             slot: BindingSlot,
+                             source: InputSource,
+                             modifiers: Modifiers| {
+                                let waiting = capture.as_ref().is_some_and(|capture| {
+                                    capture.action == *action
+                                        && capture.index == index
+                                        && capture.slot == slot
+                                });
+                                let label = if waiting {
+                                    egui::RichText::new("…")
+                                } else {
+                                    let text = format!(
+                                        "{}{}",
+                                        modifiers_label(modifiers),
+                                        input_source_label(source)
+                                    );
+                                    if conflicting(source, modifiers) {
+                                        egui::RichText::new(text)
+                                            .color(egui::Color32::from_rgb(230, 120, 80))
+                                    } else {
+                                        egui::RichText::new(text)
+                                    }
+                                };
+                                if ui.button(label).clicked() {
+                                    new_capture = Some(BindingCapture {
+                                        action: action.clone(),
+                                        index,
+                                        slot,
+                                    });
+                                }
+                            };
+                        match *binding {
+                            Binding::Button { source, modifiers } => {
+                                slot_button(ui, BindingSlot::Button, source, modifiers);
+                            }
+                            Binding::Axis { positive, negative } => {
+                                slot_button(
+                                    ui,
+                                    BindingSlot::Positive,
+                                    positive,
+                                    Modifiers::default(),
+                                );
+                                ui.label("/");
+                                slot_button(
+                                    ui,
+                                    BindingSlot::Negative,
+                                    negative,
+                                    Modifiers::default(),
+                                );
+                            }
+                        }
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(index);
+                        }
+                        ui.separator();
+                    }
+                    if let Some(index) = removed {
+                        bindings.remove(index);
+                    }
+                    if ui.small_button("➕").clicked() {
+                        new_capture = Some(BindingCapture {
+                            action: action.clone(),
+                            index: bindings.len(),
+                            slot: BindingSlot::Button,
+                        });
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    if new_capture.is_some() {
+        action_map.capture = new_capture;
+    }
+}
+
+/// The per-user path of the input bindings file
+pub fn bindings_path() -> Option<std::path::PathBuf> {
+    Some(config_directory()?.join("bindings.json"))
+}
+
+/// Loads the input bindings from disk, keeping the defaults for actions it doesn't mention
+pub fn load_action_map_system(context: &mut Context) {
+    let Some(path) = bindings_path() else {
+        return;
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    match serde_json::from_str::<ActionMap>(&contents) {
+        Ok(action_map) => {
+            context
+                .resources
+                .action_map
+                .bindings
+                .extend(action_map.bindings);
+            log::info!("Loaded input bindings from {}", path.display());
+        }
+        Err(error) => {
+            log::error!("Failed to parse input bindings {}: {error}", path.display());
+        }
+    }
+}
+
+/// Writes the input bindings to disk
+pub fn save_action_map_system(context: &mut Context) {
+    save_action_map(&context.resources.action_map);
+}
+
+fn save_action_map(action_map: &ActionMap) {
+    let Some(path) = bindings_path() else {
+        return;
+    };
+    let contents = match serde_json::to_string_pretty(action_map) {
+        Ok(contents) => contents,
+        Err(error) => {
+            log::error!("Failed to serialize input bindings: {error}");
+            return;
+        }
+    };
+    if let Some(directory) = path.parent() {
+        if let Err(error) = std::fs::create_dir_all(directory) {
+            log::error!("Failed to create config directory: {error}");
+            return;
+        }
+    }
+    if let Err(error) = std::fs::write(&path, contents) {
+        log::error!("Failed to write input bindings {}: {error}", path.display());
+    }
+}
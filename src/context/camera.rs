@@ -1,12 +1,19 @@
 use crate::context::{
+    actions::{
+        is_action_active, is_action_triggered, query_action_value, BOOST, FOCUS_SELECTION, LOOK,
+        MOVE_FORWARD, MOVE_RIGHT, MOVE_UP, PAN, ROLL, ZOOM,
+    },
     add_components, get_component, get_component_mut,
-    graphics::query_viewport_aspect_ratio,
-    input, query_entities,
-    transform::{GlobalTransform, LocalTransform},
-    Context, EntityId, CAMERA, GLOBAL_TRANSFORM, LOCAL_TRANSFORM,
+    graphics::{query_viewport_aspect_ratio, RenderMode},
+    paint::{paint_line, Lines, Painting, Quads},
+    query_entities,
+    transform::{query_global_transform, query_render_transform, LocalTransform},
+    tree::{query_descendents, query_parent},
+    ui::query_selected_entities,
+    Context, EntityId, CAMERA, CAMERA_CONTROLLER, LINES, LOCAL_TRANSFORM, QUADS,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Camera {
     pub projection: Projection,
     pub fov: f32,
@@ -29,11 +36,74 @@ impl Camera {
                 camera.y_fov_rad = self.fov.to_radians();
                 camera.matrix(aspect_ratio)
             }
-            Projection::Orthographic(camera) => camera.matrix(),
+            Projection::Orthographic(camera) => camera.matrix(aspect_ratio),
         }
     }
 }
 
+/// Makes a camera respond to mouse and keyboard input while it is the active camera
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CameraController {
+    pub mode: CameraControllerMode,
+
+    /// Radians of rotation per pixel the mouse moves
+    pub look_sensitivity: f32,
+
+    /// Panning per pixel the mouse moves, scaled by the distance to the pivot
+    pub pan_sensitivity: f32,
+
+    /// The fraction of the distance to the pivot zoomed per scroll line
+    pub zoom_sensitivity: f32,
+
+    /// The top fly speed in units per second
+    pub speed: f32,
+
+    /// How quickly the fly speed is reached in units per second squared, or instantly at zero
+    pub acceleration: f32,
+
+    /// Multiplies the fly speed while shift is held
+    pub boost: f32,
+
+    /// The point orbited around, which focusing moves to the selection
+    pub pivot: nalgebra_glm::Vec3,
+
+    /// The distance from the camera to the pivot
+    pub distance: f32,
+
+    /// The current fly velocity in world space
+    #[serde(skip)]
+    pub velocity: nalgebra_glm::Vec3,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            mode: CameraControllerMode::default(),
+            look_sensitivity: 0.005,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+            speed: 10.0,
+            acceleration: 40.0,
+            boost: 3.0,
+            pivot: nalgebra_glm::Vec3::zeros(),
+            distance: 5.0,
+            velocity: nalgebra_glm::Vec3::zeros(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CameraControllerMode {
+    /// Right drag orbits around the pivot, middle drag pans and scrolling zooms
+    Orbit,
+    /// Right drag looks around, middle drag pans and the keyboard flies
+    #[default]
+    Fly,
+    /// Dragging pans in the view plane and scrolling zooms, without rotating
+    Pan2d,
+}
+
 #[derive(Default, Debug, Copy, Clone)]
 pub struct CameraMatrices {
     pub camera_position: nalgebra_glm::Vec3,
@@ -41,7 +111,7 @@ pub struct CameraMatrices {
     pub view: nalgebra_glm::Mat4,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Projection {
     Perspective(PerspectiveCamera),
     Orthographic(OrthographicCamera),
@@ -53,7 +123,7 @@ impl Default for Projection {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PerspectiveCamera {
     pub aspect_ratio: Option<f32>,
     pub y_fov_rad: f32,
@@ -88,36 +158,148 @@ impl PerspectiveCamera {
     }
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct OrthographicCamera {
-    pub x_mag: f32,
+    /// Half the height of the view volume in world units, the width follows the aspect ratio
     pub y_mag: f32,
+    /// A fixed aspect ratio, or the viewport's when `None`
+    pub aspect_ratio: Option<f32>,
     pub z_far: f32,
     pub z_near: f32,
 }
 
+impl Default for OrthographicCamera {
+    fn default() -> Self {
+        Self {
+            y_mag: 5.0,
+            aspect_ratio: None,
+            z_far: 1000.0,
+            z_near: 0.01,
+        }
+    }
+}
+
 impl OrthographicCamera {
-    pub fn matrix(&self) -> nalgebra_glm::Mat4 {
-        let z_sum = self.z_near + self.z_far;
-        let z_diff = self.z_near - self.z_far;
-        nalgebra_glm::Mat4::new(
-            1.0 / self.x_mag,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0 / self.y_mag,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            2.0 / z_diff,
-            0.0,
-            0.0,
-            0.0,
-            z_sum / z_diff,
-            1.0,
-        )
+    pub fn matrix(&self, viewport_aspect_ratio: f32) -> nalgebra_glm::Mat4 {
+        let aspect_ratio = self.aspect_ratio.unwrap_or(viewport_aspect_ratio);
+        let y_mag = self.y_mag.max(MIN_ORTHOGRAPHIC_SIZE);
+        let x_mag = y_mag * aspect_ratio;
+        nalgebra_glm::ortho_rh_zo(-x_mag, x_mag, -y_mag, y_mag, self.z_near, self.z_far)
+    }
+}
+
+/// The smallest half height an orthographic camera zooms to
+const MIN_ORTHOGRAPHIC_SIZE: f32 = 0.01;
+
+/// Standard views a scene pane's camera can snap to, like the quad view of a modeling tool
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViewPreset {
+    Top,
+    Front,
+    Side,
+    Perspective,
+}
+
+impl ViewPreset {
+    pub const ALL: [Self; 4] = [Self::Top, Self::Front, Self::Side, Self::Perspective];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Top => "Top",
+            Self::Front => "Front",
+            Self::Side => "Side",
+            Self::Perspective => "Perspective",
+        }
+    }
+
+    /// The direction the camera looks and its up vector
+    fn orientation(&self) -> (nalgebra_glm::Vec3, nalgebra_glm::Vec3) {
+        match self {
+            Self::Top => (-nalgebra_glm::Vec3::y(), -nalgebra_glm::Vec3::z()),
+            Self::Front => (-nalgebra_glm::Vec3::z(), nalgebra_glm::Vec3::y()),
+            Self::Side => (-nalgebra_glm::Vec3::x(), nalgebra_glm::Vec3::y()),
+            Self::Perspective => (
+                nalgebra_glm::normalize(&nalgebra_glm::vec3(-1.0, -0.7, -1.0)),
+                nalgebra_glm::Vec3::y(),
+            ),
+        }
+    }
+}
+
+/// Points a camera along a preset view around its controller's pivot. The axis views switch
+/// to an orthographic projection framing the same area and to panning without rotation,
+/// the perspective view switches back to a perspective projection and orbiting.
+pub fn apply_view_preset(context: &mut Context, camera_entity: EntityId, preset: ViewPreset) {
+    let Some(local_transform) =
+        get_component::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM).copied()
+    else {
+        return;
+    };
+    let controller =
+        get_component::<CameraController>(context, camera_entity, CAMERA_CONTROLLER).cloned();
+    let (pivot, distance) = match &controller {
+        Some(controller) => (controller.pivot, controller.distance),
+        None => {
+            let distance = CameraController::default().distance;
+            (
+                local_transform.translation + local_transform.forward_vector() * distance,
+                distance,
+            )
+        }
+    };
+
+    let Some(camera) = get_component_mut::<Camera>(context, camera_entity, CAMERA) else {
+        return;
+    };
+    let half_fov = (camera.fov.to_radians() * 0.5).tan();
+    let distance = match (preset, &camera.projection) {
+        (ViewPreset::Perspective, Projection::Orthographic(orthographic)) => {
+            // Back off to where the perspective view frames the same area
+            let distance = orthographic.y_mag / half_fov;
+            camera.projection = Projection::Perspective(PerspectiveCamera::default());
+            distance
+        }
+        (ViewPreset::Top | ViewPreset::Front | ViewPreset::Side, Projection::Perspective(_)) => {
+            camera.projection = Projection::Orthographic(OrthographicCamera {
+                y_mag: distance * half_fov,
+                ..Default::default()
+            });
+            distance
+        }
+        _ => distance,
+    };
+    set_view(context, camera_entity, preset, pivot, distance);
+}
+
+fn set_view(
+    context: &mut Context,
+    camera_entity: EntityId,
+    preset: ViewPreset,
+    pivot: nalgebra_glm::Vec3,
+    distance: f32,
+) {
+    let (direction, up) = preset.orientation();
+    if let Some(local_transform) =
+        get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
+    {
+        let right = nalgebra_glm::normalize(&nalgebra_glm::cross(&direction, &up));
+        let up = nalgebra_glm::cross(&right, &direction);
+        local_transform.rotation =
+            nalgebra_glm::mat3_to_quat(&nalgebra_glm::Mat3::from_columns(&[right, up, -direction]));
+        local_transform.translation = pivot - direction * distance;
+    }
+    if let Some(controller) =
+        get_component_mut::<CameraController>(context, camera_entity, CAMERA_CONTROLLER)
+    {
+        controller.pivot = pivot;
+        controller.distance = distance;
+        controller.velocity = nalgebra_glm::Vec3::zeros();
+        controller.mode = if preset == ViewPreset::Perspective {
+            CameraControllerMode::Orbit
+        } else {
+            CameraControllerMode::Pan2d
+        };
     }
 }
 
@@ -127,16 +309,16 @@ pub fn query_active_camera_matrices(context: &Context) -> Option<CameraMatrices>
 }
 
 pub fn query_camera_matrices(context: &Context, camera_entity: EntityId) -> Option<CameraMatrices> {
-    let (Some(camera), Some(local_transform), Some(global_transform)) = (
+    let (Some(camera), Some(local_transform), Some(render_transform)) = (
         get_component::<Camera>(context, camera_entity, CAMERA),
         get_component::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM),
-        get_component::<GlobalTransform>(context, camera_entity, GLOBAL_TRANSFORM),
+        query_render_transform(context, camera_entity),
     ) else {
         return None;
     };
 
     let normalized_rotation = local_transform.rotation.normalize();
-    let camera_translation = global_transform.0.column(3).xyz();
+    let camera_translation = render_transform.column(3).xyz();
     let target = camera_translation
         + nalgebra_glm::quat_rotate_vec3(&normalized_rotation, &(-nalgebra_glm::Vec3::z()));
     let up = nalgebra_glm::quat_rotate_vec3(&normalized_rotation, &nalgebra_glm::Vec3::y());
@@ -150,6 +332,104 @@ pub fn query_camera_matrices(context: &Context, camera_entity: EntityId) -> Opti
     })
 }
 
+/// How far the frustum of an infinite perspective camera is drawn
+pub const FRUSTUM_DISPLAY_DISTANCE: f32 = 10.0;
+
+/// The depth of the camera icon drawn at each camera's position
+const CAMERA_ICON_SIZE: f32 = 0.5;
+
+/// Query for the world space corners of a camera's view volume, the near plane then the far
+/// plane, each counterclockwise from the bottom left. Infinite perspective cameras are clipped
+/// at `FRUSTUM_DISPLAY_DISTANCE`.
+pub fn query_frustum_corners(
+    camera: &Camera,
+    global_transform: &nalgebra_glm::Mat4,
+    aspect_ratio: f32,
+) -> [nalgebra_glm::Vec3; 8] {
+    let projection = camera.projection_matrix(aspect_ratio);
+    let inverse_projection = nalgebra_glm::inverse(&projection);
+    let (z_near, z_far) = match &camera.projection {
+        Projection::Perspective(perspective) => (
+            perspective.z_near,
+            perspective.z_far.unwrap_or(FRUSTUM_DISPLAY_DISTANCE),
+        ),
+        Projection::Orthographic(orthographic) => (orthographic.z_near, orthographic.z_far),
+    };
+
+    // Depths go through the projection so any depth range convention is respected
+    let ndc_depth = |distance: f32| {
+        let clip = projection * nalgebra_glm::vec4(0.0, 0.0, -distance, 1.0);
+        clip.z / clip.w
+    };
+    let mut corners = [nalgebra_glm::Vec3::zeros(); 8];
+    for (plane, distance) in [z_near, z_far].into_iter().enumerate() {
+        let depth = ndc_depth(distance);
+        for (index, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let view = inverse_projection * nalgebra_glm::vec4(x, y, depth, 1.0);
+            let world = global_transform * (view / view.w);
+            corners[plane * 4 + index] = world.xyz();
+        }
+    }
+    corners
+}
+
+/// Paints a camera's view volume with an icon at its position,
+/// whose triangle marks the camera's up direction
+pub fn paint_camera_frustum(
+    painting: &mut Painting,
+    camera: &Camera,
+    global_transform: &nalgebra_glm::Mat4,
+    aspect_ratio: f32,
+    color: nalgebra_glm::Vec4,
+) {
+    let corners = query_frustum_corners(camera, global_transform, aspect_ratio);
+    for index in 0..4 {
+        let next = (index + 1) % 4;
+        paint_line(painting, corners[index], corners[next], color);
+        paint_line(painting, corners[index + 4], corners[next + 4], color);
+        paint_line(painting, corners[index], corners[index + 4], color);
+    }
+
+    let to_world =
+        |x: f32, y: f32, z: f32| (global_transform * nalgebra_glm::vec4(x, y, z, 1.0)).xyz();
+    let half_height = CAMERA_ICON_SIZE * 0.4;
+    let half_width = half_height * aspect_ratio;
+    let origin = to_world(0.0, 0.0, 0.0);
+    let base = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .map(|(x, y)| to_world(x * half_width, y * half_height, -CAMERA_ICON_SIZE));
+    for index in 0..4 {
+        paint_line(painting, origin, base[index], color);
+        paint_line(painting, base[index], base[(index + 1) % 4], color);
+    }
+    let up = [
+        to_world(-half_width * 0.5, half_height * 1.2, -CAMERA_ICON_SIZE),
+        to_world(0.0, half_height * 1.8, -CAMERA_ICON_SIZE),
+        to_world(half_width * 0.5, half_height * 1.2, -CAMERA_ICON_SIZE),
+    ];
+    paint_line(painting, up[0], up[1], color);
+    paint_line(painting, up[1], up[2], color);
+    paint_line(painting, up[2], up[0], color);
+}
+
+/// Query for the view and projection of a camera drawn into a pane,
+/// using the pane's aspect ratio
+pub fn query_pane_camera_matrices(
+    context: &Context,
+    camera_entity: EntityId,
+    pane_rect: egui::Rect,
+) -> Option<CameraMatrices> {
+    let camera = get_component::<Camera>(context, camera_entity, CAMERA)?;
+    let global_transform = query_render_transform(context, camera_entity)?;
+    Some(CameraMatrices {
+        camera_position: global_transform.column(3).xyz(),
+        projection: camera.projection_matrix(pane_rect.width() / pane_rect.height().max(1.0)),
+        view: nalgebra_glm::inverse(&global_transform),
+    })
+}
+
 /// Pure query function - only returns the nth camera entity
 pub fn query_nth_camera(context: &Context, index: usize) -> Option<EntityId> {
     query_entities(context, CAMERA).get(index).copied()
@@ -182,19 +462,13 @@ pub fn initialize_camera_transform(context: &mut Context, camera_entity: EntityI
     }
 }
 
-/// System that ensures all cameras have proper initialization
-pub fn ensure_camera_transform_system(context: &mut Context) {
-    let camera_entities: Vec<_> = query_entities(context, CAMERA)
-        .into_iter()
-        .filter(|entity| {
-            get_component::<LocalTransform>(context, *entity, LOCAL_TRANSFORM).is_none()
-        })
-        .collect();
-
-    for entity in camera_entities {
-        add_components(context, entity, LOCAL_TRANSFORM);
-        initialize_camera_transform(context, entity);
+/// Hook that gives a camera an initialized transform when it is added without one
+pub fn on_camera_added(context: &mut Context, camera_entity: EntityId) {
+    if get_component::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM).is_some() {
+        return;
     }
+    add_components(context, camera_entity, LOCAL_TRANSFORM);
+    initialize_camera_transform(context, camera_entity);
 }
 
 pub fn query_nth_camera_matrices(context: &mut Context, index: usize) -> Option<CameraMatrices> {
@@ -203,126 +477,426 @@ pub fn query_nth_camera_matrices(context: &mut Context, index: usize) -> Option<
     Some(matrices)
 }
 
-pub fn wasd_keyboard_controls_system(context: &mut Context) {
+/// Moves the active camera's fly controller with the movement actions, scheduled as a fixed update.
+/// By default WASD moves, Space or E rises, Q or Ctrl sinks, Z and C roll and Shift boosts.
+pub fn fly_camera_system(context: &mut Context) {
     let Some(camera_entity) = context.resources.active_camera_entity else {
         return;
     };
-    let delta_time = context.resources.window.delta_time;
-    let speed = 10.0 * delta_time;
-
-    let (
-        left_key_pressed,
-        right_key_pressed,
-        forward_key_pressed,
-        backward_key_pressed,
-        up_key_pressed,
-    ) = {
-        let keyboard = &context.resources.input.keyboard;
-        (
-            keyboard.is_key_pressed(winit::keyboard::KeyCode::KeyA),
-            keyboard.is_key_pressed(winit::keyboard::KeyCode::KeyD),
-            keyboard.is_key_pressed(winit::keyboard::KeyCode::KeyW),
-            keyboard.is_key_pressed(winit::keyboard::KeyCode::KeyS),
-            keyboard.is_key_pressed(winit::keyboard::KeyCode::Space),
-        )
-    };
+    let delta_time = context.resources.time.fixed_delta_time();
 
-    let Some(local_transform) =
-        get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
-    else {
+    let forward_input = query_action_value(context, MOVE_FORWARD);
+    let right_input = query_action_value(context, MOVE_RIGHT);
+    let up_input = query_action_value(context, MOVE_UP);
+    let roll_input = query_action_value(context, ROLL);
+    let boosting = is_action_active(context, BOOST);
+
+    let (Some(mut controller), Some(mut local_transform)) = (
+        get_component::<CameraController>(context, camera_entity, CAMERA_CONTROLLER).cloned(),
+        get_component::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM).copied(),
+    ) else {
         return;
     };
+    if controller.mode != CameraControllerMode::Fly {
+        return;
+    }
 
-    let forward = local_transform.forward_vector();
-    let right = local_transform.right_vector();
-    let up = local_transform.up_vector();
+    let direction = local_transform.forward_vector() * forward_input
+        + local_transform.right_vector() * right_input
+        + local_transform.up_vector() * up_input;
+    let target_velocity = if direction == nalgebra_glm::Vec3::zeros() {
+        nalgebra_glm::Vec3::zeros()
+    } else {
+        let boost = if boosting { controller.boost } else { 1.0 };
+        nalgebra_glm::normalize(&direction) * controller.speed * boost
+    };
+    let velocity_change = target_velocity - controller.velocity;
+    let max_velocity_change = controller.acceleration * delta_time;
+    controller.velocity = if controller.acceleration <= 0.0
+        || nalgebra_glm::length(&velocity_change) <= max_velocity_change
+    {
+        target_velocity
+    } else {
+        controller.velocity + nalgebra_glm::normalize(&velocity_change) * max_velocity_change
+    };
 
-    if forward_key_pressed {
-        local_transform.translation += forward * speed;
+    local_transform.translation += controller.velocity * delta_time;
+    controller.pivot += controller.velocity * delta_time;
+    if roll_input != 0.0 {
+        local_transform.rotation *= nalgebra_glm::quat_angle_axis(
+            roll_input * FLY_ROLL_SPEED * delta_time,
+            &nalgebra_glm::Vec3::z(),
+        );
     }
-    if backward_key_pressed {
-        local_transform.translation -= forward * speed;
+
+    write_camera_controller(context, camera_entity, controller, local_transform);
+}
+
+/// Radians per second the fly controller rolls
+const FLY_ROLL_SPEED: f32 = std::f32::consts::FRAC_PI_2;
+
+/// The closest an orbit controller can zoom to its pivot
+const MIN_ORBIT_DISTANCE: f32 = 0.01;
+
+/// Rotates, pans and zooms the active camera with the mouse according to its controller mode
+pub fn camera_controller_system(context: &mut Context) {
+    let Some(camera_entity) = context.resources.active_camera_entity else {
+        return;
+    };
+    let mouse = context.resources.input.mouse;
+    let (Some(mut controller), Some(mut local_transform)) = (
+        get_component::<CameraController>(context, camera_entity, CAMERA_CONTROLLER).cloned(),
+        get_component::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM).copied(),
+    ) else {
+        return;
+    };
+
+    // A grabbed cursor looks without holding the look action, using raw mouse motion
+    let cursor_grabbed = context.resources.window.cursor_grabbed;
+    let mouse_delta = if cursor_grabbed {
+        mouse.raw_delta
+    } else {
+        mouse.position_delta
+    };
+    let right_dragging = is_action_active(context, LOOK);
+    let middle_dragging = is_action_active(context, PAN);
+    let looking = right_dragging || cursor_grabbed;
+    let scroll = query_action_value(context, ZOOM);
+
+    match controller.mode {
+        CameraControllerMode::Orbit => {
+            if looking {
+                rotate_camera(&mut local_transform, &controller, mouse_delta);
+            }
+            if middle_dragging {
+                pan_camera(&mut local_transform, &mut controller, mouse_delta);
+            }
+            if scroll != 0.0 {
+                let zoom = 1.0 - scroll * controller.zoom_sensitivity;
+                controller.distance = (controller.distance * zoom).max(MIN_ORBIT_DISTANCE);
+                zoom_orthographic(context, camera_entity, zoom);
+            }
+            local_transform.translation =
+                controller.pivot - local_transform.forward_vector() * controller.distance;
+        }
+        CameraControllerMode::Fly => {
+            if looking {
+                rotate_camera(&mut local_transform, &controller, mouse_delta);
+            }
+            if middle_dragging {
+                pan_camera(&mut local_transform, &mut controller, mouse_delta);
+            }
+            // Scrolling adjusts the fly speed
+            if scroll != 0.0 {
+                controller.speed = (controller.speed * 1.1_f32.powf(scroll)).max(0.1);
+            }
+            controller.pivot = local_transform.translation
+                + local_transform.forward_vector() * controller.distance;
+        }
+        CameraControllerMode::Pan2d => {
+            if right_dragging || middle_dragging {
+                pan_camera(&mut local_transform, &mut controller, mouse_delta);
+            }
+            if scroll != 0.0 {
+                let zoom = 1.0 - scroll * controller.zoom_sensitivity;
+                if !zoom_orthographic(context, camera_entity, zoom) {
+                    let distance = (controller.distance * zoom).max(MIN_ORBIT_DISTANCE);
+                    local_transform.translation +=
+                        local_transform.forward_vector() * (controller.distance - distance);
+                    controller.distance = distance;
+                }
+            }
+        }
     }
 
-    if left_key_pressed {
-        local_transform.translation -= right * speed;
+    write_camera_controller(context, camera_entity, controller, local_transform);
+}
+
+/// Scales the view of an orthographic camera, returning false for other cameras
+fn zoom_orthographic(context: &mut Context, camera_entity: EntityId, zoom: f32) -> bool {
+    let Some(Camera {
+        projection: Projection::Orthographic(orthographic),
+        ..
+    }) = get_component_mut::<Camera>(context, camera_entity, CAMERA)
+    else {
+        return false;
+    };
+    orthographic.y_mag = (orthographic.y_mag * zoom).max(MIN_ORTHOGRAPHIC_SIZE);
+    true
+}
+
+/// Yaws around the world up axis and pitches around the camera's right axis,
+/// stopping short of looking straight up or down
+fn rotate_camera(
+    local_transform: &mut LocalTransform,
+    controller: &CameraController,
+    mouse_delta: nalgebra_glm::Vec2,
+) {
+    let delta = -mouse_delta * controller.look_sensitivity;
+
+    let yaw = nalgebra_glm::quat_angle_axis(delta.x, &nalgebra_glm::Vec3::y());
+    local_transform.rotation = yaw * local_transform.rotation;
+
+    let current_pitch = local_transform.forward_vector().y.asin();
+    if (current_pitch + delta.y).abs() <= 89_f32.to_radians() {
+        let pitch = nalgebra_glm::quat_angle_axis(delta.y, &nalgebra_glm::Vec3::x());
+        local_transform.rotation *= pitch;
     }
-    if right_key_pressed {
-        local_transform.translation += right * speed;
+}
+
+/// Moves the camera and its pivot in the view plane, faster when further from the pivot
+fn pan_camera(
+    local_transform: &mut LocalTransform,
+    controller: &mut CameraController,
+    mouse_delta: nalgebra_glm::Vec2,
+) {
+    let scale = controller.pan_sensitivity * controller.distance.max(1.0);
+    let offset = (local_transform.up_vector() * mouse_delta.y
+        - local_transform.right_vector() * mouse_delta.x)
+        * scale;
+    local_transform.translation += offset;
+    controller.pivot += offset;
+}
+
+fn write_camera_controller(
+    context: &mut Context,
+    camera_entity: EntityId,
+    controller: CameraController,
+    local_transform: LocalTransform,
+) {
+    if let Some(current) =
+        get_component_mut::<CameraController>(context, camera_entity, CAMERA_CONTROLLER)
+    {
+        *current = controller;
     }
-    if up_key_pressed {
-        local_transform.translation += up * speed;
+    if let Some(current) =
+        get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
+    {
+        *current = local_transform;
     }
 }
 
-/// Updates the active camera's orientation using
-/// mouse controls for orbiting and panning
-pub fn look_camera_system(context: &mut Context) {
+/// Frames the selection with the active camera when the focus action is pressed in edit mode
+pub fn receive_window_event(context: &mut Context, event: &winit::event::WindowEvent) {
+    if context.resources.graphics.render_mode != RenderMode::Edit
+        || context.resources.user_interface.consumed_event
+    {
+        return;
+    }
+    if is_action_triggered(context, FOCUS_SELECTION, event) {
+        focus_selection(context);
+    }
+}
+
+/// Moves the active camera so the bounds of the selected entities fill its view,
+/// and makes the center of the bounds the controller's pivot
+pub fn focus_selection(context: &mut Context) {
     let Some(camera_entity) = context.resources.active_camera_entity else {
         return;
     };
-    let (_local_transform_matrix, _, right, up) = {
-        let Some(local_transform) =
-            get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
-        else {
-            return;
-        };
-        let local_transform_matrix = local_transform.as_matrix();
-        let forward = local_transform.forward_vector();
-        let right = local_transform.right_vector();
-        let up = local_transform.up_vector();
-        (local_transform_matrix, forward, right, up)
+    let selected_entities = query_selected_entities(context);
+    let Some((min, max)) = query_world_bounds(context, &selected_entities) else {
+        return;
+    };
+    let center = (min + max) * 0.5;
+    let radius = (nalgebra_glm::distance(&min, &max) * 0.5).max(0.5);
+
+    // The bounds are in world space, the camera's transform and pivot in its parent's
+    let world_from_parent = query_parent(context, camera_entity)
+        .map_or_else(nalgebra_glm::Mat4::identity, |parent| {
+            query_global_transform(context, parent)
+        });
+    let parent_from_world = nalgebra_glm::inverse(&world_from_parent);
+    let to_parent = |point: nalgebra_glm::Vec3| {
+        (parent_from_world * nalgebra_glm::vec4(point.x, point.y, point.z, 1.0)).xyz()
+    };
+
+    let Some(camera) = get_component_mut::<Camera>(context, camera_entity, CAMERA) else {
+        return;
+    };
+    let distance = match &mut camera.projection {
+        Projection::Perspective(_) => radius / (camera.fov.to_radians() * 0.5).sin(),
+        Projection::Orthographic(orthographic) => {
+            orthographic.y_mag = radius;
+            radius * 2.0
+        }
     };
 
-    if context
-        .resources
-        .input
-        .mouse
-        .state
-        .contains(input::MouseState::RIGHT_CLICKED)
+    let Some(local_transform) =
+        get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
+    else {
+        return;
+    };
+    let forward = local_transform.forward_vector();
+    let world_forward = (world_from_parent
+        * nalgebra_glm::vec4(forward.x, forward.y, forward.z, 0.0))
+    .xyz()
+    .normalize();
+    let pivot = to_parent(center);
+    local_transform.translation = to_parent(center - world_forward * distance);
+    let distance = nalgebra_glm::distance(&local_transform.translation, &pivot);
+
+    if let Some(controller) =
+        get_component_mut::<CameraController>(context, camera_entity, CAMERA_CONTROLLER)
     {
-        let mut delta =
-            context.resources.input.mouse.position_delta * context.resources.window.delta_time;
-        delta.x *= -1.0;
-        delta.y *= -1.0;
-
-        let Some(local_transform) =
-            get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
-        else {
-            return;
+        controller.pivot = pivot;
+        controller.distance = distance;
+        controller.velocity = nalgebra_glm::Vec3::zeros();
+    }
+}
+
+/// Query for the world space bounds of entities and their descendents,
+/// covering their origins, lines and quads but not cameras
+pub fn query_world_bounds(
+    context: &Context,
+    entities: &[EntityId],
+) -> Option<(nalgebra_glm::Vec3, nalgebra_glm::Vec3)> {
+    let mut points = Vec::new();
+    for entity in entities
+        .iter()
+        .flat_map(|entity| query_descendents(context, *entity))
+    {
+        if get_component::<Camera>(context, entity, CAMERA).is_some()
+            || get_component::<LocalTransform>(context, entity, LOCAL_TRANSFORM).is_none()
+        {
+            continue;
+        }
+        let global_transform = query_global_transform(context, entity);
+        let transform_point = |point: nalgebra_glm::Vec3| {
+            (global_transform * nalgebra_glm::vec4(point.x, point.y, point.z, 1.0)).xyz()
         };
+        points.push(transform_point(nalgebra_glm::Vec3::zeros()));
+        if let Some(Lines(lines)) = get_component::<Lines>(context, entity, LINES) {
+            for line in lines {
+                points.push(transform_point(line.start));
+                points.push(transform_point(line.end));
+            }
+        }
+        if let Some(Quads(quads)) = get_component::<Quads>(context, entity, QUADS) {
+            for quad in quads {
+                for (x, y) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+                    points.push(transform_point(
+                        quad.offset + nalgebra_glm::vec3(x * quad.size.x, y * quad.size.y, 0.0),
+                    ));
+                }
+            }
+        }
+    }
 
-        let yaw = nalgebra_glm::quat_angle_axis(delta.x, &nalgebra_glm::Vec3::y());
-        local_transform.rotation = yaw * local_transform.rotation;
+    let first = *points.first()?;
+    Some(
+        points
+            .into_iter()
+            .fold((first, first), |(min, max), point| {
+                (
+                    nalgebra_glm::min2(&min, &point),
+                    nalgebra_glm::max2(&max, &point),
+                )
+            }),
+    )
+}
 
-        let forward = local_transform.forward_vector();
-        let current_pitch = forward.y.asin();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::spawn_entities;
 
-        let new_pitch = current_pitch + delta.y;
-        if new_pitch.abs() <= 89_f32.to_radians() {
-            let pitch = nalgebra_glm::quat_angle_axis(delta.y, &nalgebra_glm::Vec3::x());
-            local_transform.rotation *= pitch;
+    fn assert_near(actual: nalgebra_glm::Vec3, expected: nalgebra_glm::Vec3) {
+        assert!(
+            nalgebra_glm::distance(&actual, &expected) < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn focus_under_a_transformed_scene() {
+        let mut context = Context::default();
+        let [scene, camera, target] = spawn_entities(&mut context, LOCAL_TRANSFORM, 3)[..] else {
+            unreachable!()
+        };
+        *get_component_mut::<LocalTransform>(&mut context, scene, LOCAL_TRANSFORM).unwrap() =
+            LocalTransform {
+                translation: nalgebra_glm::vec3(10.0, -2.0, 5.0),
+                rotation: nalgebra_glm::quat_angle_axis(1.2, &nalgebra_glm::Vec3::y()),
+                scale: nalgebra_glm::vec3(2.0, 2.0, 2.0),
+            };
+        get_component_mut::<LocalTransform>(&mut context, target, LOCAL_TRANSFORM)
+            .unwrap()
+            .translation = nalgebra_glm::vec3(1.0, 0.0, 3.0);
+        for child in [camera, target] {
+            crate::context::tree::reparent_entity(&mut context, child, Some(scene), false);
         }
+        add_components(&mut context, camera, CAMERA | CAMERA_CONTROLLER);
+        context.resources.active_camera_entity = Some(camera);
+        context.resources.user_interface.selected_entity = Some(target);
+
+        focus_selection(&mut context);
+
+        let target_position = query_global_transform(&context, target).column(3).xyz();
+        let camera_transform = query_global_transform(&context, camera);
+        let camera_position = camera_transform.column(3).xyz();
+        let camera_forward = -camera_transform.column(2).xyz().normalize();
+        assert_near(
+            (target_position - camera_position).normalize(),
+            camera_forward,
+        );
+        let controller =
+            get_component::<CameraController>(&context, camera, CAMERA_CONTROLLER).unwrap();
+        let pivot = query_global_transform(&context, scene)
+            * nalgebra_glm::vec4(
+                controller.pivot.x,
+                controller.pivot.y,
+                controller.pivot.z,
+                1.0,
+            );
+        assert_near(pivot.xyz(), target_position);
     }
 
-    if context
-        .resources
-        .input
-        .mouse
-        .state
-        .contains(input::MouseState::MIDDLE_CLICKED)
-    {
-        let mut delta =
-            context.resources.input.mouse.position_delta * context.resources.window.delta_time;
-        delta.x *= -1.0;
-        delta.y *= -1.0;
-
-        let Some(local_transform) =
-            get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
-        else {
-            return;
+    #[test]
+    fn frustum_corners() {
+        let identity = nalgebra_glm::Mat4::identity();
+
+        let perspective = Camera {
+            projection: Projection::Perspective(PerspectiveCamera {
+                z_far: Some(100.0),
+                z_near: 1.0,
+                ..Default::default()
+            }),
+            fov: 90.0,
+        };
+        let corners = query_frustum_corners(&perspective, &identity, 2.0);
+        assert_near(corners[0], nalgebra_glm::vec3(-2.0, -1.0, -1.0));
+        assert_near(corners[6], nalgebra_glm::vec3(200.0, 100.0, -100.0));
+
+        let infinite = Camera {
+            projection: Projection::Perspective(PerspectiveCamera {
+                z_near: 1.0,
+                ..Default::default()
+            }),
+            fov: 90.0,
+        };
+        let corners = query_frustum_corners(&infinite, &identity, 1.0);
+        assert_near(
+            corners[5],
+            nalgebra_glm::vec3(
+                FRUSTUM_DISPLAY_DISTANCE,
+                -FRUSTUM_DISPLAY_DISTANCE,
+                -FRUSTUM_DISPLAY_DISTANCE,
+            ),
+        );
+
+        let orthographic = Camera {
+            projection: Projection::Orthographic(OrthographicCamera {
+                y_mag: 3.0,
+                aspect_ratio: None,
+                z_far: 50.0,
+                z_near: 0.5,
+            }),
+            fov: 45.0,
         };
-        local_transform.translation += right * delta.x;
-        local_transform.translation += up * delta.y;
+        let translation = nalgebra_glm::translation(&nalgebra_glm::vec3(1.0, 2.0, 3.0));
+        let corners = query_frustum_corners(&orthographic, &translation, 4.0 / 3.0);
+        assert_near(corners[0], nalgebra_glm::vec3(-3.0, -1.0, 2.5));
+        assert_near(corners[6], nalgebra_glm::vec3(5.0, 5.0, -47.0));
     }
 }
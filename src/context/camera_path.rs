@@ -0,0 +1,455 @@
+use crate::context::{
+    get_component, get_component_mut,
+    graphics::RenderMode,
+    paint::{paint_box, paint_line, Line, Painting},
+    query_entities,
+    transform::{query_global_transform, LocalTransform},
+    tree::query_parent,
+    Context, EntityId, CAMERA, CAMERA_PATH, LOCAL_TRANSFORM,
+};
+
+/// Keyframed camera poses that the sequencer plays through the active camera in run mode.
+/// Keyframes are in the path entity's space, and the path's preview lines are generated from them.
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CameraPath {
+    /// Sorted by time
+    pub keyframes: Vec<CameraKeyframe>,
+    pub interpolation: PathInterpolation,
+    /// Wraps around to the first keyframe instead of stopping at the last
+    pub looping: bool,
+    /// The curve, keyframes and playhead, drawn alongside the entity's own `Lines`
+    #[serde(skip)]
+    pub preview_lines: Vec<Line>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CameraKeyframe {
+    /// Seconds from the start of the path
+    pub time: f32,
+    pub translation: nalgebra_glm::Vec3,
+    pub rotation: nalgebra_glm::Quat,
+    /// The vertical field of view in degrees, applied to perspective cameras
+    pub fov: f32,
+    /// The offset of the Bezier control points, leaving along it and arriving against it.
+    /// Zero eases in and out of the keyframe.
+    #[serde(default)]
+    pub handle: nalgebra_glm::Vec3,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PathInterpolation {
+    /// Passes smoothly through every keyframe using its neighbors as tangents
+    #[default]
+    CatmullRom,
+    /// Cubic Bezier segments shaped by each keyframe's handle
+    Bezier,
+}
+
+/// A camera pose sampled from a path, in the path entity's space
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraPose {
+    pub translation: nalgebra_glm::Vec3,
+    pub rotation: nalgebra_glm::Quat,
+    pub fov: f32,
+}
+
+impl CameraPose {
+    pub fn as_matrix(&self) -> nalgebra_glm::Mat4 {
+        nalgebra_glm::translation(&self.translation)
+            * nalgebra_glm::quat_to_mat4(&self.rotation.normalize())
+    }
+}
+
+impl CameraPath {
+    /// The time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keyframes
+            .last()
+            .map_or(0.0, |keyframe| keyframe.time.max(0.0))
+    }
+
+    /// Inserts a keyframe in time order, replacing any keyframe at the same time
+    pub fn insert_keyframe(&mut self, keyframe: CameraKeyframe) {
+        match self
+            .keyframes
+            .iter()
+            .position(|existing| (existing.time - keyframe.time).abs() < KEYFRAME_TIME_EPSILON)
+        {
+            Some(index) => self.keyframes[index] = keyframe,
+            None => {
+                let index = self
+                    .keyframes
+                    .partition_point(|existing| existing.time < keyframe.time);
+                self.keyframes.insert(index, keyframe);
+            }
+        }
+    }
+
+    /// Restores time order after keyframe times are edited
+    pub fn sort_keyframes(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Samples the path at a time, clamped to the keyframes or wrapped when looping
+    pub fn sample(&self, time: f32) -> Option<CameraPose> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        let time = if self.looping && last.time > first.time {
+            first.time + (time - first.time).rem_euclid(last.time - first.time)
+        } else {
+            time.clamp(first.time, last.time)
+        };
+
+        let index = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time)
+            .clamp(1, self.keyframes.len().max(2) - 1)
+            - 1;
+        let start = &self.keyframes[index];
+        let Some(end) = self.keyframes.get(index + 1) else {
+            return Some(start.pose());
+        };
+        let span = end.time - start.time;
+        let alpha = if span > 0.0 {
+            ((time - start.time) / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        Some(self.sample_segment(index, alpha))
+    }
+
+    /// Samples the segment from a keyframe to the next, at an alpha from 0 to 1
+    fn sample_segment(&self, index: usize, alpha: f32) -> CameraPose {
+        let start = &self.keyframes[index];
+        let end = &self.keyframes[index + 1];
+        let rotation = nalgebra_glm::quat_slerp(&start.rotation, &end.rotation, alpha);
+        match self.interpolation {
+            PathInterpolation::CatmullRom => {
+                let before = &self.keyframes[index.saturating_sub(1)];
+                let after = self.keyframes.get(index + 2).unwrap_or(end);
+                CameraPose {
+                    translation: catmull_rom(
+                        before.translation,
+                        start.translation,
+                        end.translation,
+                        after.translation,
+                        alpha,
+                    ),
+                    rotation,
+                    fov: catmull_rom(before.fov, start.fov, end.fov, after.fov, alpha),
+                }
+            }
+            PathInterpolation::Bezier => CameraPose {
+                translation: cubic_bezier(
+                    start.translation,
+                    start.translation + start.handle,
+                    end.translation - end.handle,
+                    end.translation,
+                    alpha,
+                ),
+                rotation,
+                fov: cubic_bezier(start.fov, start.fov, end.fov, end.fov, alpha),
+            },
+        }
+    }
+}
+
+impl CameraKeyframe {
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            translation: self.translation,
+            rotation: self.rotation,
+            fov: self.fov,
+        }
+    }
+}
+
+fn catmull_rom<T>(before: T, start: T, end: T, after: T, alpha: f32) -> T
+where
+    T: Copy
+        + std::ops::Add<Output = T>
+        + std::ops::Sub<Output = T>
+        + std::ops::Mul<f32, Output = T>,
+{
+    let alpha_squared = alpha * alpha;
+    let alpha_cubed = alpha_squared * alpha;
+    (start * 2.0
+        + (end - before) * alpha
+        + (before * 2.0 - start * 5.0 + end * 4.0 - after) * alpha_squared
+        + (start * 3.0 - before - end * 3.0 + after) * alpha_cubed)
+        * 0.5
+}
+
+fn cubic_bezier<T>(start: T, control_start: T, control_end: T, end: T, alpha: f32) -> T
+where
+    T: Copy + std::ops::Add<Output = T> + std::ops::Mul<f32, Output = T>,
+{
+    let inverse = 1.0 - alpha;
+    start * (inverse * inverse * inverse)
+        + control_start * (3.0 * inverse * inverse * alpha)
+        + control_end * (3.0 * inverse * alpha * alpha)
+        + end * (alpha * alpha * alpha)
+}
+
+/// A resource for scrubbing and playing a camera path through the active camera
+#[derive(Default)]
+pub struct Sequencer {
+    /// The camera path shown on the timeline
+    pub path_entity: Option<EntityId>,
+
+    /// The playhead in seconds from the start of the path
+    pub time: f32,
+
+    pub playing: bool,
+
+    /// The playhead when the path last drove the camera, so the camera is
+    /// only taken over while playing or after scrubbing
+    pub applied_time: Option<f32>,
+}
+
+/// Builds a keyframe at a time from a camera's current pose, in the path entity's space
+pub fn query_camera_keyframe(
+    context: &Context,
+    path_entity: EntityId,
+    camera_entity: EntityId,
+    time: f32,
+) -> Option<CameraKeyframe> {
+    let camera = get_component::<crate::context::camera::Camera>(context, camera_entity, CAMERA)?;
+    let relative_transform = LocalTransform::from_matrix(
+        &(nalgebra_glm::inverse(&query_global_transform(context, path_entity))
+            * query_global_transform(context, camera_entity)),
+    );
+    Some(CameraKeyframe {
+        time,
+        translation: relative_transform.translation,
+        rotation: relative_transform.rotation,
+        fov: camera.fov,
+        handle: nalgebra_glm::Vec3::zeros(),
+    })
+}
+
+/// Adds a keyframe at the playhead from the active camera's pose
+pub fn add_keyframe_from_active_camera(context: &mut Context) -> bool {
+    let Some(path_entity) = context.resources.sequencer.path_entity else {
+        return false;
+    };
+    let Some(camera_entity) = context.resources.active_camera_entity else {
+        return false;
+    };
+    let time = context.resources.sequencer.time;
+    let Some(keyframe) = query_camera_keyframe(context, path_entity, camera_entity, time) else {
+        return false;
+    };
+    let Some(path) = get_component_mut::<CameraPath>(context, path_entity, CAMERA_PATH) else {
+        return false;
+    };
+    path.insert_keyframe(keyframe);
+    true
+}
+
+/// Moves a camera to a pose in a path entity's space, keeping the camera's scale
+pub fn apply_camera_pose(
+    context: &mut Context,
+    path_entity: EntityId,
+    camera_entity: EntityId,
+    pose: &CameraPose,
+) {
+    let world_matrix = query_global_transform(context, path_entity) * pose.as_matrix();
+    let parent_matrix = query_parent(context, camera_entity)
+        .map(|parent| query_global_transform(context, parent))
+        .unwrap_or_else(nalgebra_glm::Mat4::identity);
+    let relative_transform =
+        LocalTransform::from_matrix(&(nalgebra_glm::inverse(&parent_matrix) * world_matrix));
+
+    if let Some(local_transform) =
+        get_component_mut::<LocalTransform>(context, camera_entity, LOCAL_TRANSFORM)
+    {
+        local_transform.translation = relative_transform.translation;
+        local_transform.rotation = relative_transform.rotation;
+    }
+    if let Some(camera) =
+        get_component_mut::<crate::context::camera::Camera>(context, camera_entity, CAMERA)
+    {
+        if matches!(
+            camera.projection,
+            crate::context::camera::Projection::Perspective(_)
+        ) {
+            camera.fov = pose.fov;
+        }
+    }
+}
+
+/// Advances the playhead and moves the active camera along the sequencer's path
+/// while playing or after scrubbing. Scheduled to run only in run mode,
+/// after the camera controllers so the path wins.
+pub fn camera_path_playback_system(context: &mut Context) {
+    let delta_time = context.resources.window.delta_time;
+    let Some(path_entity) = context.resources.sequencer.path_entity else {
+        return;
+    };
+    let Some(path) = get_component::<CameraPath>(context, path_entity, CAMERA_PATH) else {
+        context.resources.sequencer.playing = false;
+        return;
+    };
+    let duration = path.duration();
+    let looping = path.looping;
+
+    let sequencer = &mut context.resources.sequencer;
+    if sequencer.playing {
+        sequencer.time += delta_time;
+        if sequencer.time >= duration {
+            if looping && duration > 0.0 {
+                sequencer.time %= duration;
+            } else {
+                sequencer.time = duration;
+                sequencer.playing = false;
+            }
+        }
+    } else if sequencer.applied_time == Some(sequencer.time) {
+        return;
+    }
+    sequencer.applied_time = Some(sequencer.time);
+    let time = sequencer.time;
+
+    let Some(pose) = get_component::<CameraPath>(context, path_entity, CAMERA_PATH)
+        .and_then(|path| path.sample(time))
+    else {
+        return;
+    };
+    let Some(camera_entity) = context.resources.active_camera_entity else {
+        return;
+    };
+    if camera_entity == path_entity {
+        return;
+    }
+    apply_camera_pose(context, path_entity, camera_entity, &pose);
+}
+
+/// Regenerates the preview lines of every camera path, showing the curve, keyframes and playhead.
+/// Paths are hidden in run mode so they stay out of the final image.
+pub fn update_camera_path_lines_system(context: &mut Context) {
+    let running = context.resources.graphics.render_mode == RenderMode::Run;
+    for entity in query_entities(context, CAMERA_PATH) {
+        let sequencer = &context.resources.sequencer;
+        let playhead = (sequencer.path_entity == Some(entity)).then_some(sequencer.time);
+        let Some(path) = get_component_mut::<CameraPath>(context, entity, CAMERA_PATH) else {
+            continue;
+        };
+        let mut painting = Painting::default();
+        if !running {
+            paint_camera_path(&mut painting, path, playhead);
+        }
+        path.preview_lines = painting.lines;
+    }
+}
+
+fn paint_camera_path(painting: &mut Painting, path: &CameraPath, playhead: Option<f32>) {
+    for index in 0..path.keyframes.len().saturating_sub(1) {
+        let mut previous = path.keyframes[index].translation;
+        for step in 1..=PATH_SEGMENT_STEPS {
+            let alpha = step as f32 / PATH_SEGMENT_STEPS as f32;
+            let point = path.sample_segment(index, alpha).translation;
+            paint_line(painting, previous, point, PATH_COLOR);
+            previous = point;
+        }
+    }
+
+    for keyframe in &path.keyframes {
+        paint_pose_marker(painting, &keyframe.pose(), 0.15, KEYFRAME_COLOR);
+        if path.interpolation == PathInterpolation::Bezier {
+            paint_line(
+                painting,
+                keyframe.translation - keyframe.handle,
+                keyframe.translation + keyframe.handle,
+                HANDLE_COLOR,
+            );
+        }
+    }
+
+    if let Some(pose) = playhead.and_then(|time| path.sample(time)) {
+        paint_pose_marker(painting, &pose, 0.25, PLAYHEAD_COLOR);
+    }
+}
+
+/// A box at the pose with a line along its view direction
+fn paint_pose_marker(
+    painting: &mut Painting,
+    pose: &CameraPose,
+    size: f32,
+    color: nalgebra_glm::Vec4,
+) {
+    let forward = nalgebra_glm::quat_rotate_vec3(
+        &pose.rotation.normalize(),
+        &nalgebra_glm::vec3(0.0, 0.0, -1.0),
+    );
+    paint_box(
+        painting,
+        pose.translation,
+        nalgebra_glm::vec3(size, size, size),
+        color,
+    );
+    paint_line(
+        painting,
+        pose.translation,
+        pose.translation + forward * size * 4.0,
+        color,
+    );
+}
+
+/// Keyframes closer together than this share a time
+const KEYFRAME_TIME_EPSILON: f32 = 1e-3;
+
+/// The lines each segment of a path is drawn with
+const PATH_SEGMENT_STEPS: usize = 24;
+
+const PATH_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(1.0, 0.8, 0.2, 1.0);
+const KEYFRAME_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(1.0, 0.55, 0.1, 1.0);
+const HANDLE_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(0.6, 0.6, 0.6, 1.0);
+const PLAYHEAD_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(1.0, 1.0, 1.0, 1.0);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, x: f32, fov: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            translation: nalgebra_glm::vec3(x, 0.0, 0.0),
+            rotation: nalgebra_glm::Quat::identity(),
+            fov,
+            handle: nalgebra_glm::Vec3::zeros(),
+        }
+    }
+
+    #[test]
+    fn sample_camera_path() {
+        let mut path = CameraPath::default();
+        assert!(path.sample(0.0).is_none());
+
+        path.insert_keyframe(keyframe(2.0, 4.0, 60.0));
+        path.insert_keyframe(keyframe(0.0, 0.0, 40.0));
+        path.insert_keyframe(keyframe(1.0, 1.0, 50.0));
+        path.insert_keyframe(keyframe(1.0, 2.0, 50.0));
+        assert_eq!(path.keyframes.len(), 3);
+        assert_eq!(path.duration(), 2.0);
+
+        // Catmull-Rom passes through every keyframe and clamps outside them
+        for (time, x) in [(-1.0, 0.0), (0.0, 0.0), (1.0, 2.0), (2.0, 4.0), (3.0, 4.0)] {
+            let pose = path.sample(time).unwrap();
+            assert!((pose.translation.x - x).abs() < 1e-5, "{time}: {pose:?}");
+        }
+        let pose = path.sample(1.5).unwrap();
+        assert!(pose.translation.x > 2.0 && pose.translation.x < 4.0);
+        assert!(pose.fov > 50.0 && pose.fov < 60.0);
+
+        // Bezier keyframes without handles ease in and out
+        path.interpolation = PathInterpolation::Bezier;
+        let quarter = path.sample(0.25).unwrap().translation.x;
+        assert!(quarter > 0.0 && quarter < 0.5);
+        assert!((path.sample(0.5).unwrap().translation.x - 1.0).abs() < 1e-5);
+
+        path.looping = true;
+        assert_eq!(path.sample(2.5), path.sample(0.5));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::context::{graphics::RenderMode, Context};
+use std::io::Write;
+
+/// A resource for exporting the active camera's view as a sequence of frames
+#[derive(Default)]
+pub struct FrameCapture {
+    /// The capture in progress, if any
+    pub session: Option<CaptureSession>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CaptureSettings {
+    pub output: CaptureOutput,
+
+    /// The size of the captured frames in pixels, independent of the window
+    pub width: u32,
+    pub height: u32,
+
+    /// Frames per second of simulated time, each frame advances by its inverse
+    pub frame_rate: f32,
+
+    /// Stops the capture after this many frames
+    pub frame_limit: Option<u32>,
+
+    /// Plays the timeline's camera path from the start in run mode,
+    /// stopping the capture when it ends
+    pub play_camera_path: bool,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            output: CaptureOutput::default(),
+            width: 1920,
+            height: 1080,
+            frame_rate: 60.0,
+            frame_limit: None,
+            play_camera_path: false,
+        }
+    }
+}
+
+impl CaptureSettings {
+    pub fn delta_time(&self) -> f32 {
+        1.0 / self.frame_rate.max(1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CaptureOutput {
+    /// Numbered PNG files written to a directory
+    Png(std::path::PathBuf),
+    /// Raw RGBA8 frames written back to back to stdout, for piping into an encoder
+    Stdout,
+}
+
+impl Default for CaptureOutput {
+    fn default() -> Self {
+        Self::Png(std::path::PathBuf::from("capture"))
+    }
+}
+
+impl std::str::FromStr for CaptureOutput {
+    type Err = std::convert::Infallible;
+
+    /// Parses "-" as stdout and anything else as a directory
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text {
+            "-" => Self::Stdout,
+            directory => Self::Png(std::path::PathBuf::from(directory)),
+        })
+    }
+}
+
+pub struct CaptureSession {
+    pub settings: CaptureSettings,
+
+    /// The number of frames captured so far
+    pub frame: u32,
+
+    /// Stops the capture when the replay it was started alongside ends
+    pub stop_with_replay: bool,
+
+    /// Exits the app when the capture stops, for captures started from the command line
+    pub exit_when_finished: bool,
+}
+
+/// Starts capturing from the next frame, entering run mode and playing
+/// the timeline's camera path when the settings ask for it
+pub fn start_frame_capture(
+    context: &mut Context,
+    settings: CaptureSettings,
+    exit_when_finished: bool,
+) {
+    if let CaptureOutput::Png(directory) = &settings.output {
+        if let Err(error) = std::fs::create_dir_all(directory) {
+            log::error!(
+                "Failed to create capture directory {}: {error}",
+                directory.display()
+            );
+            return;
+        }
+    }
+    if settings.play_camera_path {
+        let sequencer = &mut context.resources.sequencer;
+        if sequencer.path_entity.is_some() {
+            sequencer.time = 0.0;
+            sequencer.playing = true;
+            context.resources.graphics.render_mode = RenderMode::Run;
+        } else {
+            log::warn!("No camera path on the timeline to play during the capture");
+        }
+    }
+    log::info!(
+        "Capturing {}x{} frames at {} fps to {}",
+        settings.width,
+        settings.height,
+        settings.frame_rate,
+        match &settings.output {
+            CaptureOutput::Png(directory) => directory.display().to_string(),
+            CaptureOutput::Stdout => "stdout".to_string(),
+        }
+    );
+    context.resources.frame_capture.session = Some(CaptureSession {
+        stop_with_replay: context.resources.replay.is_playing(),
+        settings,
+        frame: 0,
+        exit_when_finished,
+    });
+}
+
+pub fn stop_frame_capture(context: &mut Context) {
+    let Some(session) = context.resources.frame_capture.session.take() else {
+        return;
+    };
+    log::info!("Captured {} frames", session.frame);
+    if session.exit_when_finished {
+        context.resources.window.should_exit = true;
+    }
+}
+
+/// Replaces the frame's delta time with the capture's fixed step,
+/// so the simulation advances the same however long frames take to render and write.
+/// Runs after frame timing and replay, replayed input keeps its frames but not its timing.
+pub fn capture_timing_system(context: &mut Context) {
+    if let Some(session) = &context.resources.frame_capture.session {
+        context.resources.window.delta_time = session.settings.delta_time();
+    }
+}
+
+/// Renders and writes the frame, then stops the capture when it is finished
+pub fn capture_frame_system(context: &mut Context) {
+    let Some(session) = &context.resources.frame_capture.session else {
+        return;
+    };
+    if context.resources.active_camera_entity.is_none() {
+        log::error!("Failed to capture a frame, there is no active camera");
+        stop_frame_capture(context);
+        return;
+    }
+    let (width, height) = (
+        session.settings.width.max(1),
+        session.settings.height.max(1),
+    );
+    let Some(pixels) = crate::context::graphics::render_capture_frame(context, width, height)
+    else {
+        log::error!("Failed to render a frame for capture");
+        stop_frame_capture(context);
+        return;
+    };
+
+    let Some(session) = context.resources.frame_capture.session.as_mut() else {
+        return;
+    };
+    let written = match &session.settings.output {
+        CaptureOutput::Png(directory) => {
+            let path = directory.join(format!("frame_{:06}.png", session.frame));
+            image::save_buffer(&path, &pixels, width, height, image::ColorType::Rgba8)
+                .map_err(|error| format!("Failed to write {}: {error}", path.display()))
+        }
+        CaptureOutput::Stdout => {
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(&pixels)
+                .and_then(|()| stdout.flush())
+                .map_err(|error| format!("Failed to write the frame to stdout: {error}"))
+        }
+    };
+    if let Err(error) = written {
+        log::error!("{error}");
+        stop_frame_capture(context);
+        return;
+    }
+    session.frame += 1;
+
+    let finished = session
+        .settings
+        .frame_limit
+        .is_some_and(|frame_limit| session.frame >= frame_limit)
+        || (session.stop_with_replay && !context.resources.replay.is_playing())
+        || (session.settings.play_camera_path
+            && context.resources.sequencer.path_entity.is_some()
+            && !context.resources.sequencer.playing);
+    if finished {
+        stop_frame_capture(context);
+    }
+}
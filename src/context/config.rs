@@ -0,0 +1,120 @@
+use crate::context::{
+    ui::{save_panel_states, Pane},
+    Context,
+};
+
+/// Editor layout and preferences that persist between sessions
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// The window size and position when the editor was last closed
+    pub window: Option<WindowConfig>,
+
+    /// The tile tree layout when the editor was last closed
+    pub tile_tree: Option<egui_tiles::Tree<Pane>>,
+
+    /// Named tile tree layouts saved by the user
+    pub layouts: std::collections::BTreeMap<String, egui_tiles::Tree<Pane>>,
+
+    pub theme: Option<egui::ThemePreference>,
+    pub uniform_scaling: bool,
+    pub keep_world_transform: bool,
+    pub hide_camera_frustums: bool,
+    pub orphan_policy: crate::context::tree::OrphanPolicy,
+    pub lock_cursor_in_run_mode: bool,
+}
+
+#[derive(Default, Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The per-user directory holding the editor's config files
+pub fn config_directory() -> Option<std::path::PathBuf> {
+    Some(dirs::config_dir()?.join("abyssal"))
+}
+
+/// The per-user path of the editor config file
+pub fn config_path() -> Option<std::path::PathBuf> {
+    Some(config_directory()?.join("editor.json"))
+}
+
+/// Loads the editor config from disk, falling back to defaults
+pub fn load_editor_config_system(context: &mut Context) {
+    let Some(path) = config_path() else {
+        return;
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    match serde_json::from_str::<EditorConfig>(&contents) {
+        Ok(config) => {
+            context.resources.editor_config = config;
+            log::info!("Loaded editor config from {}", path.display());
+        }
+        Err(error) => {
+            log::error!("Failed to parse editor config {}: {error}", path.display());
+        }
+    }
+}
+
+/// Captures the current editor state and writes it to disk
+pub fn save_editor_config_system(context: &mut Context) {
+    capture_editor_config(context);
+
+    let Some(path) = config_path() else {
+        return;
+    };
+    let contents = match serde_json::to_string_pretty(&context.resources.editor_config) {
+        Ok(contents) => contents,
+        Err(error) => {
+            log::error!("Failed to serialize editor config: {error}");
+            return;
+        }
+    };
+    if let Some(directory) = path.parent() {
+        if let Err(error) = std::fs::create_dir_all(directory) {
+            log::error!("Failed to create config directory: {error}");
+            return;
+        }
+    }
+    if let Err(error) = std::fs::write(&path, contents) {
+        log::error!("Failed to write editor config {}: {error}", path.display());
+    }
+}
+
+/// Copies the current layout and preferences into the editor config resource
+pub fn capture_editor_config(context: &mut Context) {
+    save_panel_states(context);
+    let user_interface = &context.resources.user_interface;
+    let config = &mut context.resources.editor_config;
+
+    config.tile_tree = user_interface.tile_tree.clone();
+    config.uniform_scaling = user_interface.uniform_scaling;
+    config.keep_world_transform = user_interface.keep_world_transform;
+    config.hide_camera_frustums = user_interface.hide_camera_frustums;
+    config.orphan_policy = context.resources.orphan_policy;
+    config.lock_cursor_in_run_mode = context.resources.window.lock_cursor_in_run_mode;
+    if let Some(gui_state) = user_interface.state.as_ref() {
+        config.theme = Some(
+            gui_state
+                .egui_ctx()
+                .options(|options| options.theme_preference),
+        );
+    }
+
+    if let Some(window_handle) = context.resources.window.handle.as_ref() {
+        let winit::dpi::PhysicalSize { width, height } = window_handle.inner_size();
+        let winit::dpi::PhysicalPosition { x, y } =
+            window_handle.outer_position().unwrap_or_default();
+        config.window = Some(WindowConfig {
+            width,
+            height,
+            x,
+            y,
+        });
+    }
+}
@@ -0,0 +1,150 @@
+use crate::context::Context;
+
+/// The number of log records kept for the console before the oldest are dropped
+const CAPACITY: usize = 10_000;
+
+/// A resource holding the log records captured for the console.
+/// The installed logger holds a clone and appends to the same buffer.
+#[derive(Default, Clone)]
+pub struct LogSink(std::sync::Arc<std::sync::Mutex<LogBuffer>>);
+
+#[derive(Default)]
+struct LogBuffer {
+    records: std::collections::VecDeque<LogRecord>,
+
+    /// Incremented whenever the records change, so views can tell when to refresh
+    generation: u64,
+}
+
+/// The filters applied by a console panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleFilter {
+    /// The most verbose level shown
+    pub level_filter: log::LevelFilter,
+
+    /// Only show records whose module path contains this text
+    pub module_filter: String,
+
+    /// Only show records whose message contains this text
+    pub search: String,
+}
+
+impl Default for ConsoleFilter {
+    fn default() -> Self {
+        Self {
+            level_filter: log::LevelFilter::Info,
+            module_filter: String::new(),
+            search: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    /// Seconds since the logger was installed
+    pub timestamp: f32,
+    pub level: log::Level,
+    pub module: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            timestamp,
+            level,
+            module,
+            message,
+        } = self;
+        write!(f, "[{timestamp:>9.3}s {level:<5} {module}] {message}")
+    }
+}
+
+/// Forwards records to stderr through `env_logger` and
+/// tees them into the console's log sink
+struct ConsoleLogger {
+    stderr: env_logger::Logger,
+    sink: LogSink,
+    start: std::time::Instant,
+}
+
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::Level::Info || self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let log_record = LogRecord {
+            timestamp: self.start.elapsed().as_secs_f32(),
+            level: record.level(),
+            module: record.module_path().unwrap_or_default().to_string(),
+            message: record.args().to_string(),
+        };
+        let LogSink(buffer) = &self.sink;
+        let Ok(mut buffer) = buffer.lock() else {
+            return;
+        };
+        if buffer.records.len() == CAPACITY {
+            buffer.records.pop_front();
+        }
+        buffer.records.push_back(log_record);
+        buffer.generation += 1;
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+/// Installs the global logger, replacing `env_logger::init`.
+/// Records are captured into the given sink, normally `context.resources.log_sink`.
+pub fn initialize_logger(sink: LogSink) {
+    let stderr = env_logger::Builder::from_default_env().build();
+    let max_level = stderr.filter().max(log::LevelFilter::Info);
+    let logger = ConsoleLogger {
+        stderr,
+        sink,
+        start: std::time::Instant::now(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Query for the generation of the captured records, which changes whenever they do
+pub fn query_log_generation(context: &Context) -> u64 {
+    let LogSink(buffer) = &context.resources.log_sink;
+    buffer.lock().map_or(0, |buffer| buffer.generation)
+}
+
+/// Query for the captured records that pass a console's filters
+pub fn query_log_records(context: &Context, filter: &ConsoleFilter) -> Vec<LogRecord> {
+    let LogSink(buffer) = &context.resources.log_sink;
+    let Ok(buffer) = buffer.lock() else {
+        return Vec::new();
+    };
+    let search = filter.search.to_lowercase();
+    buffer
+        .records
+        .iter()
+        .filter(|record| record.level <= filter.level_filter)
+        .filter(|record| record.module.contains(&filter.module_filter))
+        .filter(|record| search.is_empty() || record.message.to_lowercase().contains(&search))
+        .cloned()
+        .collect()
+}
+
+/// Discards every captured record
+pub fn clear_log_records(context: &Context) {
+    let LogSink(buffer) = &context.resources.log_sink;
+    if let Ok(mut buffer) = buffer.lock() {
+        buffer.records.clear();
+        buffer.generation += 1;
+    }
+}
@@ -5,16 +5,18 @@ mod quads;
 mod sky;
 
 use crate::context::{
-    camera::{Camera, CameraMatrices},
+    camera::{paint_camera_frustum, query_pane_camera_matrices, Camera, CameraMatrices},
+    get_component,
     graphics::{lines::LineInstance, quads::QuadInstance},
-    paint::{Lines, Quads},
-    transform::GlobalTransform,
-    tree::{is_descendant_of, Parent},
-    ui::PaneKind,
-    Context,
+    paint::{Line, Lines, Painting, Quads},
+    query_entities, query_entities_filtered,
+    transform::{query_render_transform, GlobalTransform, RenderTransform},
+    tree::{is_descendant_of, ROOT_ENTITIES},
+    ui::{query_selected_entities, PaneKind},
+    Context, EntityRef, QueryFilter, CAMERA, CAMERA_PATH, GLOBAL_TRANSFORM, LINES, QUADS,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum RenderMode {
     Edit,
     Run,
@@ -37,6 +39,25 @@ pub struct Graphics {
 
     /// The current render mode
     pub render_mode: RenderMode,
+
+    /// Draw calls and instances submitted during the last rendered frame
+    pub draw_stats: DrawStats,
+
+    /// The position of the resize system in the window resized events
+    pub resize_events: crate::context::EventCursor,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct DrawStats {
+    pub draw_calls: u32,
+    pub instances: u32,
+}
+
+impl std::ops::AddAssign for DrawStats {
+    fn add_assign(&mut self, other: Self) {
+        self.draw_calls += other.draw_calls;
+        self.instances += other.instances;
+    }
 }
 
 /// Contains all resources required for rendering
@@ -45,6 +66,8 @@ pub struct Renderer {
     pub ui_depth_texture_view: wgpu::TextureView,
     pub ui: egui_wgpu::Renderer,
     pub targets: Vec<RenderTarget>,
+    /// The offscreen target frames are captured to, with its size
+    pub capture_target: Option<((u32, u32), RenderTarget)>,
 }
 
 pub struct RenderTarget {
@@ -78,6 +101,7 @@ pub fn initialize_graphics_system(context: &mut crate::context::Context) {
 }
 
 pub fn render_frame_system(context: &mut crate::context::Context) {
+    context.resources.graphics.draw_stats = DrawStats::default();
     if context.resources.graphics.viewport_size == (0, 0) {
         return;
     }
@@ -190,10 +214,7 @@ fn render_run_mode(context: &mut crate::context::Context) {
         );
 
         // Render the scene
-        sky::render_sky(&mut render_pass, &target.sky);
-        lines::render_lines(&mut render_pass, &target.lines);
-        quads::render_quads(&mut render_pass, &target.quads);
-        grid::render_grid(&mut render_pass, &target.grid);
+        context.resources.graphics.draw_stats += render_scene(&mut render_pass, target);
     }
 
     renderer.gpu.queue.submit(std::iter::once(encoder.finish()));
@@ -209,7 +230,7 @@ fn render_edit_mode(context: &mut crate::context::Context) {
         .tile_tree_context
         .viewport_tiles
         .values()
-        .copied()
+        .cloned()
         .collect::<Vec<_>>();
 
     for (_, viewport) in viewports.iter_mut() {
@@ -306,12 +327,13 @@ fn render_edit_mode(context: &mut crate::context::Context) {
         });
     }
 
+    let mut draw_stats = DrawStats::default();
     viewports
         .iter()
         .zip(renderer.targets.iter())
         .for_each(|((kind, viewport), target)| {
             let viewport_size = (viewport.width() as u32, viewport.height() as u32);
-            render_pane(&mut encoder, kind, target, viewport_size);
+            draw_stats += render_pane(&mut encoder, kind, target, viewport_size);
 
             let source_origin = wgpu::Origin3d { x: 0, y: 0, z: 0 };
             let destination_origin = wgpu::Origin3d {
@@ -370,6 +392,10 @@ fn render_edit_mode(context: &mut crate::context::Context) {
         );
     }
 
+    // The gui is drawn with one call per clipped primitive and draws no instances
+    draw_stats.draw_calls += paint_jobs.len() as u32;
+    context.resources.graphics.draw_stats = draw_stats;
+
     renderer.gpu.queue.submit(std::iter::once(encoder.finish()));
     surface_texture.present();
 }
@@ -424,11 +450,23 @@ async fn create_renderer_async(
         ui_depth_texture_view,
         ui: egui_renderer,
         targets: Vec::new(),
+        capture_target: None,
     }
 }
 
-pub fn resize_renderer_system(context: &mut crate::context::Context, width: u32, height: u32) {
-    let Some(renderer) = context.resources.graphics.renderer.as_mut() else {
+/// Resizes the surface and depth textures to the latest window size
+pub fn resize_renderer_system(context: &mut crate::context::Context) {
+    let graphics = &mut context.resources.graphics;
+    let Some(crate::context::window::WindowResized { width, height }) = context
+        .events
+        .window_resized
+        .read(&mut graphics.resize_events)
+        .last()
+        .copied()
+    else {
+        return;
+    };
+    let Some(renderer) = graphics.renderer.as_mut() else {
         return;
     };
 
@@ -484,14 +522,20 @@ pub fn resize_renderer_system(context: &mut crate::context::Context, width: u32,
 }
 
 fn create_render_target(renderer: &mut Renderer) -> RenderTarget {
+    let width = renderer.gpu.surface_config.width;
+    let height = renderer.gpu.surface_config.height;
+    create_sized_render_target(renderer, width, height)
+}
+
+fn create_sized_render_target(renderer: &Renderer, width: u32, height: u32) -> RenderTarget {
     let color_texture = renderer
         .gpu
         .device
         .create_texture(&wgpu::TextureDescriptor {
             label: Some("Viewport Texture"),
             size: wgpu::Extent3d {
-                width: renderer.gpu.surface_config.width,
-                height: renderer.gpu.surface_config.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
@@ -506,8 +550,6 @@ fn create_render_target(renderer: &mut Renderer) -> RenderTarget {
     let color_texture_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
     let device: &wgpu::Device = &renderer.gpu.device;
-    let width = renderer.gpu.surface_config.width;
-    let height = renderer.gpu.surface_config.height;
     let depth_texture = device.create_texture(
         &(wgpu::TextureDescriptor {
             label: Some("Depth Texture"),
@@ -578,7 +620,7 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
         .tile_tree_context
         .viewport_tiles
         .values()
-        .copied()
+        .cloned()
         .collect::<Vec<_>>();
 
     // Collect camera matrices
@@ -589,21 +631,7 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
             ..
         } = kind
         {
-            if let (Some(camera), Some(transform)) = (
-                get_component::<Camera>(context, *camera_entity, CAMERA),
-                get_component::<GlobalTransform>(context, *camera_entity, GLOBAL_TRANSFORM),
-            ) {
-                let view = nalgebra_glm::inverse(&transform.0);
-                let projection = camera.projection_matrix(viewport.width() / viewport.height());
-
-                Some(CameraMatrices {
-                    view,
-                    projection,
-                    camera_position: transform.0.column(3).xyz(),
-                })
-            } else {
-                None
-            }
+            query_pane_camera_matrices(context, *camera_entity, *viewport)
         } else {
             None
         };
@@ -620,50 +648,30 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
             } = kind
             {
                 // Find the scene this camera belongs to by traversing up
-                let actual_scene = if let Some(camera) = camera_entity {
-                    let mut current = *camera;
-                    let mut found_scene = None;
-                    // Keep traversing up until we find a root node
-                    while let Some(Parent(parent)) =
-                        get_component::<Parent>(context, current, PARENT)
-                    {
-                        current = *parent;
-                        // If current is a root node (no parent), this is our scene
-                        if get_component::<Parent>(context, current, PARENT).is_none() {
-                            found_scene = Some(current);
-                            break;
-                        }
-                    }
-                    found_scene
-                } else {
-                    None
-                };
+                let actual_scene =
+                    camera_entity.and_then(|camera| query_camera_scene(context, camera));
 
                 // Use the actual scene entity for rendering
                 if let Some(actual_scene) = actual_scene {
                     // Get all entities in this scene's hierarchy
-                    let scene_entities = query_entities(context, LOCAL_TRANSFORM)
+                    let scene_entities = query_components(context, SCENE_GEOMETRY)
                         .into_iter()
-                        .filter(|entity| is_descendant_of(context, *entity, actual_scene))
+                        .filter(|entity| is_descendant_of(context, entity.entity, actual_scene))
                         .collect::<Vec<_>>();
 
                     // Process lines for this scene's entities only
-                    let scene_lines: Vec<_> = scene_entities
+                    let mut scene_lines: Vec<_> = scene_entities
                         .iter()
                         .filter_map(|entity| {
-                            let Lines(lines) = get_component::<Lines>(context, *entity, LINES)?;
-                            let global_transform = get_component::<GlobalTransform>(
-                                context,
-                                *entity,
-                                GLOBAL_TRANSFORM,
-                            )?;
+                            let lines = query_entity_lines(entity);
+                            let global_transform = query_entity_render_matrix(entity)?;
 
                             Some(
                                 lines
                                     .iter()
                                     .map(|line| {
                                         // Transform line to world space
-                                        let start_world = (global_transform.0
+                                        let start_world = (global_transform
                                             * nalgebra_glm::vec4(
                                                 line.start.x,
                                                 line.start.y,
@@ -671,7 +679,7 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
                                                 1.0,
                                             ))
                                         .xyz();
-                                        let end_world = (global_transform.0
+                                        let end_world = (global_transform
                                             * nalgebra_glm::vec4(
                                                 line.end.x, line.end.y, line.end.z, 1.0,
                                             ))
@@ -698,17 +706,21 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
                         })
                         .flatten()
                         .collect();
+                    if !context.resources.user_interface.hide_camera_frustums {
+                        scene_lines.extend(collect_camera_frustum_lines(
+                            context,
+                            actual_scene,
+                            *camera_entity,
+                            &viewports,
+                        ));
+                    }
 
                     // Process quads for this scene's entities only
                     let scene_quads: Vec<_> = scene_entities
                         .iter()
                         .filter_map(|entity| {
-                            let Quads(quads) = get_component::<Quads>(context, *entity, QUADS)?;
-                            let global_transform = get_component::<GlobalTransform>(
-                                context,
-                                *entity,
-                                GLOBAL_TRANSFORM,
-                            )?;
+                            let Quads(quads) = entity.quads?;
+                            let global_transform = query_entity_render_matrix(entity)?;
                             Some(
                                 quads
                                     .iter()
@@ -724,7 +736,7 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
                                                 quad.offset.y,
                                                 quad.offset.z,
                                             ));
-                                        let final_transform = global_transform.0 * offset * scale;
+                                        let final_transform = global_transform * offset * scale;
                                         QuadInstance {
                                             model_matrix_0: final_transform.column(0).into(),
                                             model_matrix_1: final_transform.column(1).into(),
@@ -786,6 +798,7 @@ fn update_pane_uniforms_system(context: &mut crate::context::Context) {
                 }
             }
             PaneKind::Color(_color) => {}
+            PaneKind::Panel(_) => {}
             PaneKind::Empty => {}
         }
     }
@@ -796,7 +809,7 @@ fn render_pane(
     pane_kind: &PaneKind,
     target: &RenderTarget,
     viewport_size: (u32, u32),
-) {
+) -> DrawStats {
     let clear_color = match pane_kind {
         PaneKind::Scene { .. } => wgpu::Color::BLACK,
         PaneKind::Color(color) => wgpu::Color {
@@ -805,7 +818,7 @@ fn render_pane(
             b: (color.b() as f64 / 255.0),
             a: 1.0,
         },
-        PaneKind::Empty => wgpu::Color {
+        PaneKind::Panel(_) | PaneKind::Empty => wgpu::Color {
             r: 32.0 / 255.0,
             g: 32.0 / 255.0,
             b: 32.0 / 255.0,
@@ -843,7 +856,7 @@ fn render_pane(
     );
 
     if viewport.width() <= 0.0 || viewport.height() <= 0.0 {
-        return;
+        return DrawStats::default();
     }
 
     render_pass.set_viewport(
@@ -856,13 +869,31 @@ fn render_pane(
     );
 
     if matches!(pane_kind, PaneKind::Scene { .. }) {
-        sky::render_sky(&mut render_pass, &target.sky);
-        lines::render_lines(&mut render_pass, &target.lines);
-        quads::render_quads(&mut render_pass, &target.quads);
-        grid::render_grid(&mut render_pass, &target.grid);
+        render_scene(&mut render_pass, target)
+    } else {
+        DrawStats::default()
     }
 }
 
+/// Records the draw calls for a scene into a render pass
+fn render_scene(render_pass: &mut wgpu::RenderPass<'_>, target: &RenderTarget) -> DrawStats {
+    let mut draw_stats = DrawStats::default();
+    for instances in [
+        sky::render_sky(render_pass, &target.sky),
+        lines::render_lines(render_pass, &target.lines),
+        quads::render_quads(render_pass, &target.quads),
+        grid::render_grid(render_pass, &target.grid),
+    ] {
+        if instances > 0 {
+            draw_stats += DrawStats {
+                draw_calls: 1,
+                instances,
+            };
+        }
+    }
+    draw_stats
+}
+
 fn ensure_viewports(context: &mut Context, viewport_count: usize) {
     let Some(renderer) = context.resources.graphics.renderer.as_mut() else {
         return;
@@ -877,6 +908,148 @@ fn ensure_viewports(context: &mut Context, viewport_count: usize) {
     });
 }
 
+/// Renders the active camera offscreen at a size independent of the window
+/// and reads the frame back as tightly packed RGBA rows
+pub fn render_capture_frame(context: &mut Context, width: u32, height: u32) -> Option<Vec<u8>> {
+    let camera_entity = context.resources.active_camera_entity?;
+    let viewport =
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32));
+    let matrices = query_pane_camera_matrices(context, camera_entity, viewport)?;
+    let scene_data = collect_scene_data(context);
+
+    let renderer = context.resources.graphics.renderer.as_mut()?;
+    let swap_red_and_blue = match renderer.gpu.surface_config.format {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        format => {
+            log::error!("Frame capture does not support the {format:?} surface format");
+            return None;
+        }
+    };
+    if renderer
+        .capture_target
+        .as_ref()
+        .is_none_or(|(size, _)| *size != (width, height))
+    {
+        let target = create_sized_render_target(renderer, width, height);
+        renderer.capture_target = Some(((width, height), target));
+    }
+    let Renderer {
+        gpu,
+        capture_target: Some((_, target)),
+        ..
+    } = renderer
+    else {
+        return None;
+    };
+
+    grid::update_grid(&matrices, &gpu.queue, &target.grid);
+    sky::update_sky(&matrices, &gpu.queue, &target.sky);
+    if let Some((scene_lines, scene_quads)) = scene_data {
+        lines::update_lines_uniform(
+            &matrices,
+            &gpu.device,
+            &gpu.queue,
+            &mut target.lines,
+            scene_lines,
+        );
+        quads::update_quads_uniform(
+            &matrices,
+            &gpu.device,
+            &gpu.queue,
+            &mut target.quads,
+            scene_quads,
+        );
+    }
+
+    let mut encoder = gpu
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Render Encoder"),
+        });
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Capture Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.color_texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &target.depth_texture_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+        render_scene(&mut render_pass, target);
+    }
+
+    // Buffer rows are padded to the copy alignment and packed again after reading
+    let row_bytes = width as usize * 4;
+    let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+    let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Readback Buffer"),
+        size: (padded_row_bytes * height as usize) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: &target.color_texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes as u32),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    gpu.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    gpu.device.poll(wgpu::Maintain::Wait);
+    if let Err(error) = receiver.recv().ok()? {
+        log::error!("Failed to read back the captured frame: {error}");
+        return None;
+    }
+
+    let mut pixels = Vec::with_capacity(row_bytes * height as usize);
+    for row in slice.get_mapped_range().chunks(padded_row_bytes) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    buffer.unmap();
+    if swap_red_and_blue {
+        pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+    Some(pixels)
+}
+
 pub fn query_viewport_aspect_ratio(context: &crate::context::Context) -> Option<f32> {
     let Some(renderer) = &context.resources.graphics.renderer else {
         return None;
@@ -886,7 +1059,106 @@ pub fn query_viewport_aspect_ratio(context: &crate::context::Context) -> Option<
     Some(aspect_ratio)
 }
 
+/// Paints the frustum of every camera in a scene except the one a pane views it through,
+/// highlighting selected cameras
+fn collect_camera_frustum_lines(
+    context: &crate::context::Context,
+    scene_entity: crate::context::EntityId,
+    pane_camera: Option<crate::context::EntityId>,
+    viewports: &[(PaneKind, egui::Rect)],
+) -> Vec<LineInstance> {
+    let selected_entities = query_selected_entities(context);
+    let mut painting = Painting::default();
+    for camera_entity in query_entities(context, CAMERA | GLOBAL_TRANSFORM) {
+        if Some(camera_entity) == pane_camera
+            || !is_descendant_of(context, camera_entity, scene_entity)
+        {
+            continue;
+        }
+        let (Some(camera), Some(global_transform)) = (
+            get_component::<Camera>(context, camera_entity, CAMERA),
+            query_render_transform(context, camera_entity),
+        ) else {
+            continue;
+        };
+
+        // Cameras shown in a pane take its shape, others the window's
+        let aspect_ratio = viewports
+            .iter()
+            .find_map(|(kind, viewport)| match kind {
+                PaneKind::Scene {
+                    camera_entity: Some(entity),
+                    ..
+                } if *entity == camera_entity => Some(viewport.width() / viewport.height()),
+                _ => None,
+            })
+            .or_else(|| query_viewport_aspect_ratio(context))
+            .unwrap_or(4.0 / 3.0);
+        let color = if selected_entities.contains(&camera_entity) {
+            SELECTED_CAMERA_COLOR
+        } else {
+            CAMERA_COLOR
+        };
+        paint_camera_frustum(
+            &mut painting,
+            camera,
+            &global_transform,
+            aspect_ratio,
+            color,
+        );
+    }
+    painting
+        .lines
+        .into_iter()
+        .map(|line| LineInstance {
+            start: nalgebra_glm::vec4(line.start.x, line.start.y, line.start.z, 1.0),
+            end: nalgebra_glm::vec4(line.end.x, line.end.y, line.end.z, 1.0),
+            color: line.color,
+        })
+        .collect()
+}
+
+const CAMERA_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(0.8, 0.8, 0.8, 1.0);
+const SELECTED_CAMERA_COLOR: nalgebra_glm::Vec4 = nalgebra_glm::Vec4::new(1.0, 0.6, 0.1, 1.0);
+
 // Update the render_frame_system to handle different render modes
+/// Matches the entities that draw lines or quads
+const SCENE_GEOMETRY: QueryFilter =
+    QueryFilter::with(GLOBAL_TRANSFORM).any_of(LINES | QUADS | CAMERA_PATH);
+
+/// The lines an entity draws, its own followed by its camera path preview
+fn query_entity_lines<'a>(entity: &EntityRef<'a>) -> Vec<&'a Line> {
+    entity
+        .lines
+        .map(|Lines(lines)| lines.as_slice())
+        .into_iter()
+        .chain(entity.camera_path.map(|path| path.preview_lines.as_slice()))
+        .flatten()
+        .collect()
+}
+
+/// The matrix an entity is drawn with, its render transform if it has one
+fn query_entity_render_matrix(entity: &EntityRef<'_>) -> Option<nalgebra_glm::Mat4> {
+    entity
+        .render_transform
+        .map(|RenderTransform(render_transform)| *render_transform)
+        .or_else(|| {
+            entity
+                .global_transform
+                .map(|GlobalTransform(global_transform)| *global_transform)
+        })
+}
+
+/// Query for the scene at the root of the hierarchy a camera belongs to
+fn query_camera_scene(
+    context: &crate::context::Context,
+    camera_entity: crate::context::EntityId,
+) -> Option<crate::context::EntityId> {
+    query_entities_filtered(context, ROOT_ENTITIES)
+        .into_iter()
+        .find(|scene| *scene != camera_entity && is_descendant_of(context, camera_entity, *scene))
+}
+
 fn collect_scene_data(
     context: &crate::context::Context,
 ) -> Option<(Vec<LineInstance>, Vec<QuadInstance>)> {
@@ -894,44 +1166,30 @@ fn collect_scene_data(
 
     // Find the scene this camera belongs to
     let camera_entity = context.resources.active_camera_entity?;
-    let mut current = camera_entity;
-    let mut found_scene = None;
-
-    // Keep traversing up until we find a root node
-    while let Some(Parent(parent)) = get_component::<Parent>(context, current, PARENT) {
-        current = *parent;
-        // If current is a root node (no parent), this is our scene
-        if get_component::<Parent>(context, current, PARENT).is_none() {
-            found_scene = Some(current);
-            break;
-        }
-    }
-
-    let scene_entity = found_scene?;
+    let scene_entity = query_camera_scene(context, camera_entity)?;
 
     // Get all entities in this scene's hierarchy
-    let scene_entities = query_entities(context, LOCAL_TRANSFORM)
+    let scene_entities = query_components(context, SCENE_GEOMETRY)
         .into_iter()
-        .filter(|entity| is_descendant_of(context, *entity, scene_entity))
+        .filter(|entity| is_descendant_of(context, entity.entity, scene_entity))
         .collect::<Vec<_>>();
 
     // Process lines for this scene's entities
     let scene_lines: Vec<_> = scene_entities
         .iter()
         .filter_map(|entity| {
-            let Lines(lines) = get_component::<Lines>(context, *entity, LINES)?;
-            let global_transform =
-                get_component::<GlobalTransform>(context, *entity, GLOBAL_TRANSFORM)?;
+            let lines = query_entity_lines(entity);
+            let global_transform = query_entity_render_matrix(entity)?;
 
             Some(
                 lines
                     .iter()
                     .map(|line| {
                         // Transform line to world space
-                        let start_world = (global_transform.0
+                        let start_world = (global_transform
                             * nalgebra_glm::vec4(line.start.x, line.start.y, line.start.z, 1.0))
                         .xyz();
-                        let end_world = (global_transform.0
+                        let end_world = (global_transform
                             * nalgebra_glm::vec4(line.end.x, line.end.y, line.end.z, 1.0))
                         .xyz();
 
@@ -956,9 +1214,8 @@ fn collect_scene_data(
     let scene_quads: Vec<_> = scene_entities
         .iter()
         .filter_map(|entity| {
-            let Quads(quads) = get_component::<Quads>(context, *entity, QUADS)?;
-            let global_transform =
-                get_component::<GlobalTransform>(context, *entity, GLOBAL_TRANSFORM)?;
+            let Quads(quads) = entity.quads?;
+            let global_transform = query_entity_render_matrix(entity)?;
 
             Some(
                 quads
@@ -974,7 +1231,7 @@ fn collect_scene_data(
                             quad.offset.y,
                             quad.offset.z,
                         ));
-                        let final_transform = global_transform.0 * offset * scale;
+                        let final_transform = global_transform * offset * scale;
                         QuadInstance {
                             model_matrix_0: final_transform.column(0).into(),
                             model_matrix_1: final_transform.column(1).into(),
@@ -122,10 +122,12 @@ pub fn create_grid(
     }
 }
 
-pub fn render_grid(render_pass: &mut wgpu::RenderPass<'_>, grid: &Grid) {
+/// Draws the grid, returning the number of instances drawn
+pub fn render_grid(render_pass: &mut wgpu::RenderPass<'_>, grid: &Grid) -> u32 {
     render_pass.set_pipeline(&grid.pipeline);
     render_pass.set_bind_group(0, &grid.bind_group, &[]);
     render_pass.draw(0..6, 0..1);
+    1
 }
 
 pub fn update_grid(
@@ -200,7 +200,8 @@ pub fn update_lines_uniform(
     );
 }
 
-pub fn render_lines(render_pass: &mut wgpu::RenderPass<'_>, lines: &Lines) {
+/// Draws the line instances, returning the number of instances drawn
+pub fn render_lines(render_pass: &mut wgpu::RenderPass<'_>, lines: &Lines) -> u32 {
     let instance_size = std::mem::size_of::<LineInstance>();
     let debug_line_instance_count = (lines.instance_buffer.size() as usize / instance_size) as u32;
     if debug_line_instance_count > 0 {
@@ -210,4 +211,5 @@ pub fn render_lines(render_pass: &mut wgpu::RenderPass<'_>, lines: &Lines) {
         render_pass.set_vertex_buffer(1, lines.instance_buffer.slice(..));
         render_pass.draw(0..2, 0..debug_line_instance_count);
     }
+    debug_line_instance_count
 }
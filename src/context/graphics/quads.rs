@@ -224,7 +224,8 @@ pub fn update_quads_uniform(
     });
 }
 
-pub fn render_quads(render_pass: &mut wgpu::RenderPass<'_>, quads: &Quads) {
+/// Draws the quad instances, returning the number of instances drawn
+pub fn render_quads(render_pass: &mut wgpu::RenderPass<'_>, quads: &Quads) -> u32 {
     let instance_size = std::mem::size_of::<QuadInstance>();
     let instance_count = (quads.instance_buffer.size() as usize / instance_size) as u32;
     if instance_count > 0 {
@@ -235,4 +236,5 @@ pub fn render_quads(render_pass: &mut wgpu::RenderPass<'_>, quads: &Quads) {
         render_pass.set_index_buffer(quads.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
         render_pass.draw_indexed(0..6, 0, 0..instance_count);
     }
+    instance_count
 }
@@ -344,8 +344,10 @@ pub fn update_sky(
     queue.write_buffer(&sky.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
 }
 
-pub fn render_sky(render_pass: &mut wgpu::RenderPass<'_>, sky: &Sky) {
+/// Draws the sky, returning the number of instances drawn
+pub fn render_sky(render_pass: &mut wgpu::RenderPass<'_>, sky: &Sky) -> u32 {
     render_pass.set_pipeline(&sky.pipeline);
     render_pass.set_bind_group(0, &sky.bind_group, &[]);
     render_pass.draw(0..3, 0..1);
+    1
 }
@@ -1,15 +1,64 @@
-use crate::context::{graphics::RenderMode, Context};
+use crate::context::{
+    actions::{is_action_just_pressed, mouse_button_flag, InputSource, EXIT_RUN_MODE},
+    graphics::RenderMode,
+    replay::record_input_event,
+    Context,
+};
+
+/// The longest gap between two clicks of a double click
+pub const DOUBLE_CLICK_TIME: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// The furthest the cursor can move between two clicks of a double click, in pixels
+pub const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+/// The pixels of precise scrolling, such as from a touchpad, that make one scroll line
+pub const PIXELS_PER_SCROLL_LINE: f32 = 40.0;
+
+/// The number of entries kept in the input history
+pub const INPUT_HISTORY_LENGTH: usize = 64;
 
 #[derive(Default)]
 pub struct Input {
     pub keyboard: Keyboard,
     pub mouse: Mouse,
+
+    /// Recent presses and releases, oldest first
+    pub history: std::collections::VecDeque<InputHistoryEntry>,
+
+    /// Seconds of frame time the input has seen, advanced by each frame's delta time
+    /// instead of the wall clock so replayed recordings detect double clicks identically
+    pub clock: f64,
+}
+
+/// A press or release recorded in the input history
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InputHistoryEntry {
+    pub frame: u32,
+    pub source: InputSource,
+    pub edge: InputEdge,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputEdge {
+    Pressed,
+    Released,
+    DoubleClicked,
 }
 
 /// Contains keyboard-specific input state
 #[derive(Default)]
 pub struct Keyboard {
     pub keystates: std::collections::HashMap<winit::keyboard::KeyCode, winit::event::ElementState>,
+    pub modifiers: winit::keyboard::ModifiersState,
+
+    /// Keys pressed since the last frame
+    pub just_pressed: std::collections::HashSet<winit::keyboard::KeyCode>,
+
+    /// Keys released since the last frame
+    pub just_released: std::collections::HashSet<winit::keyboard::KeyCode>,
+
+    /// Held keys that sent an operating system key repeat since the last frame
+    pub repeated: std::collections::HashSet<winit::keyboard::KeyCode>,
 }
 
 impl Keyboard {
@@ -18,10 +67,14 @@ impl Keyboard {
             .get(&keycode)
             .is_some_and(|state| *state == winit::event::ElementState::Pressed)
     }
+
+    pub fn is_key_just_pressed(&self, keycode: winit::keyboard::KeyCode) -> bool {
+        self.just_pressed.contains(&keycode)
+    }
 }
 
 bitflags::bitflags! {
-    #[derive(Default, Debug, Clone, Copy)]
+    #[derive(Default, Debug, Clone, Copy, PartialEq)]
     pub struct MouseState: u8 {
         const LEFT_CLICKED = 0b0000_0001;
         const MIDDLE_CLICKED = 0b0000_0010;
@@ -35,96 +88,280 @@ bitflags::bitflags! {
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Mouse {
     pub state: MouseState,
+
+    /// Buttons pressed since the last frame
+    pub just_pressed: MouseState,
+
+    /// Buttons released since the last frame
+    pub just_released: MouseState,
+
+    /// Buttons double clicked since the last frame
+    pub double_clicked: MouseState,
+
     pub position: nalgebra_glm::Vec2,
+
+    /// Cursor movement since the last frame, summed over every move event
     pub position_delta: nalgebra_glm::Vec2,
+
+    /// Raw device motion since the last frame while the cursor is grabbed,
+    /// which keeps going when the cursor would stop at the screen edge
+    pub raw_delta: nalgebra_glm::Vec2,
+
+    /// Scroll lines since the last frame, with precise scrolling converted to lines
     pub wheel_delta: nalgebra_glm::Vec2,
+
+    /// The button, input clock time and position of the last click, for double click detection
+    pub last_click: Option<(MouseState, f64, nalgebra_glm::Vec2)>,
 }
 
+/// Releases a grabbed cursor when the exit action is pressed, or returns to edit mode
+/// when the cursor is free, scheduled to run only in run mode
 pub fn escape_key_exit_system(context: &mut crate::context::Context) {
-    let keyboard = &context.resources.input.keyboard;
-
-    if keyboard.is_key_pressed(winit::keyboard::KeyCode::Escape)
-        && matches!(context.resources.graphics.render_mode, RenderMode::Run)
-    {
+    if !is_action_just_pressed(context, EXIT_RUN_MODE) {
+        return;
+    }
+    let window = &mut context.resources.window;
+    if window.cursor_grabbed {
+        window.cursor_released = true;
+    } else {
         context.resources.graphics.render_mode = RenderMode::Edit;
     }
 }
 
-/// Resets the input state for the next frame
+/// Clears the per-frame input state for the next frame
 pub fn reset_input_system(context: &mut crate::context::Context) {
-    let mouse = &mut context.resources.input.mouse;
-    if mouse.state.contains(MouseState::SCROLLED) {
-        mouse.wheel_delta = nalgebra_glm::vec2(0.0, 0.0);
-    }
-    mouse.state.set(MouseState::MOVED, false);
-    if !mouse.state.contains(MouseState::MOVED) {
-        mouse.position_delta = nalgebra_glm::vec2(0.0, 0.0);
-    }
-    mouse.state.set(MouseState::MOVED, false);
+    let delta_time = context.resources.window.delta_time;
+    let Input {
+        keyboard,
+        mouse,
+        clock,
+        ..
+    } = &mut context.resources.input;
+    *clock += delta_time as f64;
+    keyboard.just_pressed.clear();
+    keyboard.just_released.clear();
+    keyboard.repeated.clear();
+
+    mouse.just_pressed = MouseState::empty();
+    mouse.just_released = MouseState::empty();
+    mouse.double_clicked = MouseState::empty();
+    mouse.position_delta = nalgebra_glm::vec2(0.0, 0.0);
+    mouse.raw_delta = nalgebra_glm::vec2(0.0, 0.0);
+    mouse.wheel_delta = nalgebra_glm::vec2(0.0, 0.0);
+    mouse.state.remove(MouseState::MOVED | MouseState::SCROLLED);
 }
 
+/// A window event reduced to the input it carries, so recordings can replay it
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum InputEvent {
+    Modifiers(winit::keyboard::ModifiersState),
+    Key {
+        key_code: winit::keyboard::KeyCode,
+        pressed: bool,
+        repeat: bool,
+    },
+    MouseButton {
+        button: winit::event::MouseButton,
+        pressed: bool,
+    },
+    CursorMoved(nalgebra_glm::Vec2),
+    /// Raw device motion
+    MouseMotion(nalgebra_glm::Vec2),
+    /// Scroll lines, with precise scrolling already converted to lines
+    Scrolled(nalgebra_glm::Vec2),
+}
+
+/// Applies window events to the input state, live input is ignored while a recording replays
 pub fn receive_window_event(
     context: &mut crate::context::Context,
     event: &winit::event::WindowEvent,
 ) {
-    receive_winit_keyboard_events(context, event);
-    receive_winit_mouse_events(context, event);
-}
-
-fn receive_winit_keyboard_events(context: &mut Context, event: &winit::event::WindowEvent) {
-    let winit::event::WindowEvent::KeyboardInput {
-        event:
-            winit::event::KeyEvent {
-                physical_key: winit::keyboard::PhysicalKey::Code(key_code),
-                state,
-                ..
-            },
-        ..
-    } = event
-    else {
+    let Some(input_event) = input_event_from_window_event(event) else {
         return;
     };
-    *context
-        .resources
-        .input
-        .keyboard
-        .keystates
-        .entry(*key_code)
-        .or_insert(*state) = *state;
+    receive_input_event(context, input_event);
+}
+
+/// Accumulates raw mouse motion while the cursor is grabbed
+pub fn receive_device_event(context: &mut Context, event: &winit::event::DeviceEvent) {
+    let winit::event::DeviceEvent::MouseMotion { delta: (x, y) } = event else {
+        return;
+    };
+    if !context.resources.window.cursor_grabbed {
+        return;
+    }
+    receive_input_event(
+        context,
+        InputEvent::MouseMotion(nalgebra_glm::vec2(*x as f32, *y as f32)),
+    );
 }
 
-fn receive_winit_mouse_events(context: &mut Context, event: &winit::event::WindowEvent) {
-    let mouse = &mut context.resources.input.mouse;
+fn receive_input_event(context: &mut Context, input_event: InputEvent) {
+    if context.resources.replay.is_playing() {
+        return;
+    }
+    record_input_event(context, input_event);
+    apply_input_event(context, input_event);
+}
+
+pub fn input_event_from_window_event(event: &winit::event::WindowEvent) -> Option<InputEvent> {
     match event {
+        winit::event::WindowEvent::ModifiersChanged(modifiers) => {
+            Some(InputEvent::Modifiers(modifiers.state()))
+        }
+        winit::event::WindowEvent::KeyboardInput {
+            event:
+                winit::event::KeyEvent {
+                    physical_key: winit::keyboard::PhysicalKey::Code(key_code),
+                    state,
+                    repeat,
+                    ..
+                },
+            ..
+        } => Some(InputEvent::Key {
+            key_code: *key_code,
+            pressed: *state == winit::event::ElementState::Pressed,
+            repeat: *repeat,
+        }),
         winit::event::WindowEvent::MouseInput { button, state, .. } => {
-            let clicked = *state == winit::event::ElementState::Pressed;
-            match button {
-                winit::event::MouseButton::Left => {
-                    mouse.state.set(MouseState::LEFT_CLICKED, clicked);
-                }
-                winit::event::MouseButton::Middle => {
-                    mouse.state.set(MouseState::MIDDLE_CLICKED, clicked);
+            Some(InputEvent::MouseButton {
+                button: *button,
+                pressed: *state == winit::event::ElementState::Pressed,
+            })
+        }
+        winit::event::WindowEvent::CursorMoved { position, .. } => Some(InputEvent::CursorMoved(
+            nalgebra_glm::vec2(position.x as _, position.y as _),
+        )),
+        winit::event::WindowEvent::MouseWheel { delta, .. } => {
+            Some(InputEvent::Scrolled(match delta {
+                winit::event::MouseScrollDelta::LineDelta(h_lines, v_lines) => {
+                    nalgebra_glm::vec2(*h_lines, *v_lines)
                 }
-                winit::event::MouseButton::Right => {
-                    mouse.state.set(MouseState::RIGHT_CLICKED, clicked);
+                winit::event::MouseScrollDelta::PixelDelta(position) => {
+                    nalgebra_glm::vec2(position.x as f32, position.y as f32)
+                        / PIXELS_PER_SCROLL_LINE
                 }
-                _ => {}
-            }
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Updates the input state from an input event
+pub fn apply_input_event(context: &mut Context, input_event: InputEvent) {
+    match input_event {
+        InputEvent::Modifiers(modifiers) => {
+            context.resources.input.keyboard.modifiers = modifiers;
+        }
+        InputEvent::Key {
+            key_code,
+            pressed,
+            repeat,
+        } => apply_key_event(context, key_code, pressed, repeat),
+        InputEvent::MouseButton { button, pressed } => {
+            apply_mouse_button_event(context, button, pressed)
         }
-        winit::event::WindowEvent::CursorMoved { position, .. } => {
+        InputEvent::CursorMoved(current_position) => {
+            let mouse = &mut context.resources.input.mouse;
             let last_position = mouse.position;
-            let current_position = nalgebra_glm::vec2(position.x as _, position.y as _);
             mouse.position = current_position;
-            mouse.position_delta = current_position - last_position;
-            mouse.state.set(MouseState::MOVED, true);
+            mouse.position_delta += current_position - last_position;
+            mouse.state.insert(MouseState::MOVED);
         }
-        winit::event::WindowEvent::MouseWheel {
-            delta: winit::event::MouseScrollDelta::LineDelta(h_lines, v_lines),
-            ..
-        } => {
-            mouse.wheel_delta = nalgebra_glm::vec2(*h_lines, *v_lines);
-            mouse.state.set(MouseState::SCROLLED, true);
+        InputEvent::MouseMotion(delta) => {
+            context.resources.input.mouse.raw_delta += delta;
+        }
+        InputEvent::Scrolled(lines) => {
+            let mouse = &mut context.resources.input.mouse;
+            mouse.wheel_delta += lines;
+            mouse.state.insert(MouseState::SCROLLED);
+        }
+    }
+}
+
+fn apply_key_event(
+    context: &mut Context,
+    key_code: winit::keyboard::KeyCode,
+    pressed: bool,
+    repeat: bool,
+) {
+    let keyboard = &mut context.resources.input.keyboard;
+    if repeat {
+        keyboard.repeated.insert(key_code);
+        return;
+    }
+    let was_pressed = keyboard.is_key_pressed(key_code);
+    let state = if pressed {
+        winit::event::ElementState::Pressed
+    } else {
+        winit::event::ElementState::Released
+    };
+    keyboard.keystates.insert(key_code, state);
+    match (was_pressed, pressed) {
+        (false, true) => {
+            keyboard.just_pressed.insert(key_code);
+            record_input_history(context, InputSource::Key(key_code), InputEdge::Pressed);
+        }
+        (true, false) => {
+            keyboard.just_released.insert(key_code);
+            record_input_history(context, InputSource::Key(key_code), InputEdge::Released);
         }
         _ => {}
     }
 }
+
+fn apply_mouse_button_event(
+    context: &mut Context,
+    button: winit::event::MouseButton,
+    clicked: bool,
+) {
+    let Some(flag) = mouse_button_flag(button) else {
+        return;
+    };
+    let Input { mouse, clock, .. } = &mut context.resources.input;
+    if mouse.state.contains(flag) == clicked {
+        return;
+    }
+    mouse.state.set(flag, clicked);
+    if !clicked {
+        mouse.just_released.insert(flag);
+        record_input_history(context, InputSource::Mouse(button), InputEdge::Released);
+        return;
+    }
+
+    mouse.just_pressed.insert(flag);
+    let now = *clock;
+    let double_clicked = mouse.last_click.is_some_and(|(last_flag, time, position)| {
+        last_flag == flag
+            && now - time <= DOUBLE_CLICK_TIME.as_secs_f64()
+            && nalgebra_glm::distance(&position, &mouse.position) <= DOUBLE_CLICK_DISTANCE
+    });
+    if double_clicked {
+        // A third click starts a new double click instead of completing another
+        mouse.double_clicked.insert(flag);
+        mouse.last_click = None;
+    } else {
+        mouse.last_click = Some((flag, now, mouse.position));
+    }
+    record_input_history(context, InputSource::Mouse(button), InputEdge::Pressed);
+    if double_clicked {
+        record_input_history(
+            context,
+            InputSource::Mouse(button),
+            InputEdge::DoubleClicked,
+        );
+    }
+}
+
+fn record_input_history(context: &mut Context, source: InputSource, edge: InputEdge) {
+    let frame = context.resources.window.frame_counter;
+    let history = &mut context.resources.input.history;
+    if history.len() == INPUT_HISTORY_LENGTH {
+        history.pop_front();
+    }
+    history.push_back(InputHistoryEntry {
+        frame,
+        source,
+        edge,
+    });
+}
@@ -3,20 +3,20 @@
 use crate::context::{Context, EntityId};
 use nalgebra_glm::{Vec2, Vec3, Vec4};
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Lines(pub Vec<Line>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Line {
     pub start: nalgebra_glm::Vec3,
     pub end: nalgebra_glm::Vec3,
     pub color: nalgebra_glm::Vec4,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Quads(pub Vec<Quad>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Quad {
     pub size: nalgebra_glm::Vec2,
     pub offset: nalgebra_glm::Vec3,
@@ -0,0 +1,764 @@
+use crate::context::{
+    actions::bindings_ui,
+    camera_path::{add_keyframe_from_active_camera, CameraPath},
+    capture::{start_frame_capture, stop_frame_capture, CaptureOutput, CaptureSettings},
+    console::{
+        clear_log_records, query_log_generation, query_log_records, ConsoleFilter, LogRecord,
+    },
+    get_component,
+    graphics::RenderMode,
+    profiler::{query_frame_time_stats, start_capture, stop_capture},
+    query_entities,
+    tree::Name,
+    ui::{inspector_ui, scene_tree_ui},
+    Context, CAMERA_PATH, NAME,
+};
+
+/// A dockable editor panel shown inside a tile pane
+pub trait Panel {
+    /// The text shown on the pane's tab
+    fn title(&self, context: &Context) -> String;
+
+    /// Draws the panel's contents
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui);
+
+    /// Captures the state that should persist with the layout
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restores state previously returned by `save`
+    fn load(&mut self, _state: &serde_json::Value) {}
+}
+
+pub type PanelFactory = fn() -> Box<dyn Panel>;
+
+/// A resource mapping panel names to constructors,
+/// used to populate the pane type dropdown
+pub struct PanelRegistry {
+    pub factories: std::collections::BTreeMap<String, PanelFactory>,
+}
+
+impl Default for PanelRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            factories: std::collections::BTreeMap::new(),
+        };
+        registry.register(SCENE_TREE_PANEL, || Box::<SceneTreePanel>::default());
+        registry.register(INSPECTOR_PANEL, || Box::<InspectorPanel>::default());
+        registry.register(CONSOLE_PANEL, || Box::<ConsolePanel>::default());
+        registry.register(ASSET_BROWSER_PANEL, || Box::<AssetBrowserPanel>::default());
+        registry.register(PROFILER_PANEL, || Box::<ProfilerPanel>::default());
+        registry.register(BINDINGS_PANEL, || Box::<BindingsPanel>::default());
+        registry.register(TIMELINE_PANEL, || Box::<TimelinePanel>::default());
+        registry.register(CAPTURE_PANEL, || Box::<CapturePanel>::default());
+        registry
+    }
+}
+
+impl PanelRegistry {
+    /// Registers a panel type, replacing any panel with the same name.
+    /// User-defined panels are added through `context.resources.panel_registry`.
+    pub fn register(&mut self, name: &str, factory: PanelFactory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    /// Creates a new instance of a registered panel
+    pub fn create(&self, name: &str) -> Option<Box<dyn Panel>> {
+        self.factories.get(name).map(|factory| factory())
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.factories.keys().cloned().collect()
+    }
+}
+
+pub const SCENE_TREE_PANEL: &str = "Scene Tree";
+pub const INSPECTOR_PANEL: &str = "Inspector";
+pub const CONSOLE_PANEL: &str = "Console";
+pub const ASSET_BROWSER_PANEL: &str = "Asset Browser";
+pub const PROFILER_PANEL: &str = "Profiler";
+pub const BINDINGS_PANEL: &str = "Bindings";
+pub const TIMELINE_PANEL: &str = "Timeline";
+pub const CAPTURE_PANEL: &str = "Capture";
+
+#[derive(Default)]
+pub struct SceneTreePanel;
+
+impl Panel for SceneTreePanel {
+    fn title(&self, _context: &Context) -> String {
+        SCENE_TREE_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .id_salt("scene_tree")
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                scene_tree_ui(context, ui);
+            });
+    }
+}
+
+/// Edits the input bindings of every action
+#[derive(Default)]
+pub struct BindingsPanel;
+
+impl Panel for BindingsPanel {
+    fn title(&self, _context: &Context) -> String {
+        BINDINGS_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .id_salt("bindings")
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                bindings_ui(context, ui);
+            });
+    }
+}
+
+#[derive(Default)]
+pub struct InspectorPanel;
+
+impl Panel for InspectorPanel {
+    fn title(&self, _context: &Context) -> String {
+        INSPECTOR_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .id_salt("inspector")
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                inspector_ui(context, ui);
+            });
+    }
+}
+
+/// Shows captured log records with level, module and text filters
+#[derive(Default)]
+pub struct ConsolePanel {
+    pub filter: ConsoleFilter,
+
+    /// The filtered records, with the log generation and filter they were built from.
+    /// Rebuilt only when either changes instead of on every frame.
+    records: Vec<LogRecord>,
+    records_source: Option<(u64, ConsoleFilter)>,
+}
+
+impl Panel for ConsolePanel {
+    fn title(&self, _context: &Context) -> String {
+        CONSOLE_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        let generation = query_log_generation(context);
+        if self
+            .records_source
+            .as_ref()
+            .is_none_or(|(source_generation, source_filter)| {
+                *source_generation != generation || *source_filter != self.filter
+            })
+        {
+            self.records = query_log_records(context, &self.filter);
+            self.records_source = Some((generation, self.filter.clone()));
+        }
+        let filter = &mut self.filter;
+        let records = &self.records;
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::new(ui.id().with("console_level"), "")
+                .selected_text(filter.level_filter.to_string())
+                .show_ui(ui, |ui| {
+                    for level in log::LevelFilter::iter().skip(1) {
+                        ui.selectable_value(&mut filter.level_filter, level, level.to_string());
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut filter.module_filter)
+                    .hint_text("Module")
+                    .desired_width(120.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut filter.search)
+                    .hint_text("Search")
+                    .desired_width(160.0),
+            );
+            if ui.button("Copy").clicked() {
+                let text = records
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.ctx().copy_text(text);
+            }
+            if ui.button("Clear").clicked() {
+                clear_log_records(context);
+            }
+        });
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .id_salt(ui.id().with("console"))
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, records.len(), |ui, row_range| {
+                for record in &records[row_range] {
+                    let color = match record.level {
+                        log::Level::Error => egui::Color32::from_rgb(240, 80, 80),
+                        log::Level::Warn => egui::Color32::from_rgb(220, 180, 60),
+                        log::Level::Info => ui.visuals().text_color(),
+                        log::Level::Debug => egui::Color32::from_gray(150),
+                        log::Level::Trace => egui::Color32::from_gray(110),
+                    };
+                    ui.label(
+                        egui::RichText::new(record.to_string())
+                            .monospace()
+                            .color(color),
+                    );
+                }
+            });
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({
+            "level_filter": self.filter.level_filter.to_string(),
+            "module_filter": self.filter.module_filter,
+            "search": self.filter.search,
+        })
+    }
+
+    fn load(&mut self, state: &serde_json::Value) {
+        if let Some(level_filter) = state["level_filter"]
+            .as_str()
+            .and_then(|level| level.parse().ok())
+        {
+            self.filter.level_filter = level_filter;
+        }
+        if let Some(module_filter) = state["module_filter"].as_str() {
+            self.filter.module_filter = module_filter.to_string();
+        }
+        if let Some(search) = state["search"].as_str() {
+            self.filter.search = search.to_string();
+        }
+    }
+}
+
+/// Scrubs and plays a camera path through the active camera in run mode,
+/// and keys the active camera's pose at the playhead
+#[derive(Default)]
+pub struct TimelinePanel;
+
+impl Panel for TimelinePanel {
+    fn title(&self, _context: &Context) -> String {
+        TIMELINE_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        let paths = query_entities(context, CAMERA_PATH);
+        let sequencer = &mut context.resources.sequencer;
+        if sequencer
+            .path_entity
+            .is_none_or(|path_entity| !paths.contains(&path_entity))
+        {
+            sequencer.path_entity = paths.first().copied();
+            sequencer.playing = false;
+        }
+        let Some(path_entity) = sequencer.path_entity else {
+            ui.label(
+                egui::RichText::new("Add a Camera Path component to an entity to sequence it")
+                    .color(egui::Color32::from_gray(128)),
+            );
+            return;
+        };
+
+        let path_label = |entity| match get_component::<Name>(context, entity, NAME) {
+            Some(Name(name)) => name.clone(),
+            None => format!("Entity {}", entity.id),
+        };
+        let labels = paths
+            .iter()
+            .map(|entity| (*entity, path_label(*entity)))
+            .collect::<Vec<_>>();
+        let selected_label = path_label(path_entity);
+        let (keyframe_times, duration) =
+            get_component::<CameraPath>(context, path_entity, CAMERA_PATH)
+                .map(|path| {
+                    (
+                        path.keyframes
+                            .iter()
+                            .map(|keyframe| keyframe.time)
+                            .collect::<Vec<_>>(),
+                        path.duration(),
+                    )
+                })
+                .unwrap_or_default();
+
+        let running = context.resources.graphics.render_mode == RenderMode::Run;
+        let sequencer = &mut context.resources.sequencer;
+        let mut enter_run_mode = false;
+        ui.horizontal(|ui| {
+            egui::ComboBox::new(ui.id().with("timeline_path"), "")
+                .selected_text(selected_label)
+                .show_ui(ui, |ui| {
+                    for (entity, label) in labels {
+                        if ui
+                            .selectable_value(&mut sequencer.path_entity, Some(entity), label)
+                            .clicked()
+                        {
+                            sequencer.playing = false;
+                        }
+                    }
+                });
+            if ui.button("⏮").on_hover_text("Jump to the start").clicked() {
+                sequencer.time = 0.0;
+            }
+            let play_label = if sequencer.playing { "⏸" } else { "▶" };
+            if ui
+                .button(play_label)
+                .on_hover_text("Play the path through the active camera, entering run mode")
+                .clicked()
+            {
+                sequencer.playing = !sequencer.playing;
+                if sequencer.playing && sequencer.time >= duration {
+                    sequencer.time = 0.0;
+                }
+                if sequencer.playing && !running {
+                    enter_run_mode = true;
+                }
+            }
+            ui.add(
+                egui::DragValue::new(&mut sequencer.time)
+                    .range(0.0..=duration)
+                    .speed(0.01)
+                    .suffix(" s"),
+            );
+            ui.label(format!("/ {duration:.2} s"));
+        });
+        if enter_run_mode {
+            context.resources.graphics.render_mode = RenderMode::Run;
+        } else if !running {
+            // Leaving run mode stops playback
+            context.resources.sequencer.playing = false;
+        }
+
+        timeline_ui(
+            ui,
+            &mut context.resources.sequencer.time,
+            &keyframe_times,
+            duration,
+        );
+
+        ui.horizontal(|ui| {
+            if ui
+                .button("➕ Keyframe")
+                .on_hover_text("Key the active camera's pose at the playhead")
+                .clicked()
+                && !add_keyframe_from_active_camera(context)
+            {
+                log::warn!("No active camera to add a keyframe from");
+            }
+            if !running {
+                ui.label(
+                    egui::RichText::new("The path drives the camera in run mode")
+                        .color(egui::Color32::from_gray(128)),
+                );
+            }
+        });
+    }
+}
+
+/// Draws the keyframes and playhead along a strip, dragging it scrubs the playhead
+fn timeline_ui(ui: &mut egui::Ui, time: &mut f32, keyframe_times: &[f32], duration: f32) {
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), 36.0),
+        egui::Sense::click_and_drag(),
+    );
+    let duration = duration.max(f32::EPSILON);
+    if let Some(pointer) = response.interact_pointer_pos() {
+        *time = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0) * duration;
+    }
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(24));
+    let x_for = |time: f32| rect.left() + rect.width() * (time / duration).clamp(0.0, 1.0);
+    for second in 0..=duration.floor() as u32 {
+        let x = x_for(second as f32);
+        painter.vline(
+            x,
+            rect.bottom() - 6.0..=rect.bottom(),
+            egui::Stroke::new(1.0, egui::Color32::from_gray(70)),
+        );
+    }
+    for keyframe_time in keyframe_times {
+        let center = egui::pos2(x_for(*keyframe_time), rect.center().y);
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                center + egui::vec2(0.0, -6.0),
+                center + egui::vec2(6.0, 0.0),
+                center + egui::vec2(0.0, 6.0),
+                center + egui::vec2(-6.0, 0.0),
+            ],
+            egui::Color32::from_rgb(255, 140, 25),
+            egui::Stroke::NONE,
+        ));
+    }
+    painter.vline(
+        x_for(*time),
+        rect.y_range(),
+        egui::Stroke::new(2.0, egui::Color32::WHITE),
+    );
+}
+
+/// Exports the active camera's view as numbered PNGs or a raw RGBA stream,
+/// rendered at a fixed simulated frame rate
+#[derive(Default)]
+pub struct CapturePanel {
+    pub settings: CaptureSettings,
+}
+
+impl Panel for CapturePanel {
+    fn title(&self, _context: &Context) -> String {
+        CAPTURE_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        if let Some(session) = &context.resources.frame_capture.session {
+            let progress = match session.settings.frame_limit {
+                Some(frame_limit) => format!("{} / {frame_limit}", session.frame),
+                None => session.frame.to_string(),
+            };
+            if ui
+                .button(format!("⏹ Stop Capture ({progress} frames)"))
+                .clicked()
+            {
+                stop_frame_capture(context);
+            }
+            return;
+        }
+
+        let settings = &mut self.settings;
+        ui.horizontal(|ui| {
+            ui.label("Output:");
+            let mut to_stdout = settings.output == CaptureOutput::Stdout;
+            if ui
+                .radio_value(&mut to_stdout, false, "PNG Sequence")
+                .clicked()
+            {
+                settings.output = CaptureOutput::default();
+            }
+            if ui
+                .radio_value(&mut to_stdout, true, "Raw RGBA to stdout")
+                .on_hover_text("Frames written back to back, for piping into an encoder")
+                .clicked()
+            {
+                settings.output = CaptureOutput::Stdout;
+            }
+        });
+        if let CaptureOutput::Png(directory) = &mut settings.output {
+            ui.horizontal(|ui| {
+                ui.label("Directory:");
+                let mut text = directory.display().to_string();
+                if ui.text_edit_singleline(&mut text).changed() {
+                    *directory = std::path::PathBuf::from(text);
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("Size:");
+            ui.add(egui::DragValue::new(&mut settings.width).range(1..=8192));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut settings.height).range(1..=8192));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Frame Rate:");
+            ui.add(
+                egui::DragValue::new(&mut settings.frame_rate)
+                    .range(1.0..=240.0)
+                    .suffix(" fps"),
+            );
+        });
+        ui.horizontal(|ui| {
+            let mut limited = settings.frame_limit.is_some();
+            if ui.checkbox(&mut limited, "Frame Limit").changed() {
+                settings.frame_limit = limited.then_some(settings.frame_rate.round() as u32 * 10);
+            }
+            if let Some(frame_limit) = &mut settings.frame_limit {
+                ui.add(egui::DragValue::new(frame_limit).range(1..=u32::MAX));
+            }
+        });
+        ui.checkbox(&mut settings.play_camera_path, "Play Camera Path")
+            .on_hover_text("Play the timeline's path from the start and stop when it ends");
+
+        if ui.button("⏺ Start Capture").clicked() {
+            start_frame_capture(context, self.settings.clone(), false);
+        }
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    fn load(&mut self, state: &serde_json::Value) {
+        if let Ok(settings) = serde_json::from_value(state.clone()) {
+            self.settings = settings;
+        }
+    }
+}
+
+/// How long a directory listing is shown before it is read again
+const ASSET_LISTING_LIFETIME: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Browses the files under the working directory
+#[derive(Default)]
+pub struct AssetBrowserPanel {
+    /// The directory being shown, relative to the working directory
+    pub directory: std::path::PathBuf,
+
+    /// The last listing of the directory, or the error reading it,
+    /// refreshed on a timer, on demand, or when the directory changes
+    listing: Option<AssetListing>,
+}
+
+struct AssetListing {
+    directory: std::path::PathBuf,
+    read_at: std::time::Instant,
+    entries: Result<Vec<AssetEntry>, String>,
+}
+
+struct AssetEntry {
+    name: String,
+    is_directory: bool,
+    size: u64,
+}
+
+impl Panel for AssetBrowserPanel {
+    fn title(&self, _context: &Context) -> String {
+        ASSET_BROWSER_PANEL.to_string()
+    }
+
+    fn ui(&mut self, _context: &mut Context, ui: &mut egui::Ui) {
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.directory.parent().is_some(), egui::Button::new("⬆"))
+                .on_hover_text("Parent directory")
+                .clicked()
+            {
+                self.directory.pop();
+            }
+            refresh = ui.button("⟳").on_hover_text("Refresh").clicked();
+            ui.label(format!("./{}", self.directory.display()));
+        });
+        ui.separator();
+
+        let stale = self.listing.as_ref().is_none_or(|listing| {
+            listing.directory != self.directory
+                || listing.read_at.elapsed() >= ASSET_LISTING_LIFETIME
+        });
+        if refresh || stale {
+            self.listing = Some(self.read_listing());
+        }
+        let Some(listing) = &self.listing else {
+            return;
+        };
+        let entries = match &listing.entries {
+            Ok(entries) => entries,
+            Err(error) => {
+                ui.label(
+                    egui::RichText::new(format!("Failed to read directory: {error}"))
+                        .color(egui::Color32::from_rgb(240, 80, 80)),
+                );
+                return;
+            }
+        };
+
+        let mut opened = None;
+        egui::ScrollArea::vertical()
+            .id_salt(ui.id().with("asset_browser"))
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                for entry in entries {
+                    let name = &entry.name;
+                    if entry.is_directory {
+                        if ui.selectable_label(false, format!("📁 {name}")).clicked() {
+                            opened = Some(name.clone());
+                        }
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("📄 {name}"));
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.label(
+                                        egui::RichText::new(format!("{} B", entry.size))
+                                            .color(egui::Color32::from_gray(128)),
+                                    );
+                                },
+                            );
+                        });
+                    }
+                }
+            });
+        if let Some(name) = opened {
+            self.directory.push(name);
+        }
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({ "directory": self.directory })
+    }
+
+    fn load(&mut self, state: &serde_json::Value) {
+        if let Some(directory) = state["directory"].as_str() {
+            self.directory = std::path::PathBuf::from(directory);
+        }
+    }
+}
+
+impl AssetBrowserPanel {
+    /// Reads the directory, sorting subdirectories first and then by name
+    fn read_listing(&self) -> AssetListing {
+        let entries = std::fs::read_dir(self.directory_path())
+            .map(|entries| {
+                let mut entries = entries
+                    .flatten()
+                    .map(|entry| AssetEntry {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        is_directory: entry.file_type().is_ok_and(|file_type| file_type.is_dir()),
+                        size: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort_by(|a, b| (!a.is_directory, &a.name).cmp(&(!b.is_directory, &b.name)));
+                entries
+            })
+            .map_err(|error| error.to_string());
+        AssetListing {
+            directory: self.directory.clone(),
+            read_at: std::time::Instant::now(),
+            entries,
+        }
+    }
+
+    fn directory_path(&self) -> std::path::PathBuf {
+        if self.directory.as_os_str().is_empty() {
+            std::path::PathBuf::from(".")
+        } else {
+            self.directory.clone()
+        }
+    }
+}
+
+/// Graphs frame times and shows per-system timings and renderer statistics
+#[derive(Default)]
+pub struct ProfilerPanel;
+
+impl Panel for ProfilerPanel {
+    fn title(&self, _context: &Context) -> String {
+        PROFILER_PANEL.to_string()
+    }
+
+    fn ui(&mut self, context: &mut Context, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut context.resources.profiler.paused, "Pause");
+            if context.resources.profiler.capture.is_some() {
+                let event_count = context
+                    .resources
+                    .profiler
+                    .capture
+                    .as_ref()
+                    .map_or(0, Vec::len);
+                if ui
+                    .button(format!("⏹ Stop Capture ({event_count} events)"))
+                    .clicked()
+                {
+                    stop_capture(context);
+                }
+            } else if ui
+                .button("⏺ Capture")
+                .on_hover_text("Record a Chrome trace JSON file")
+                .clicked()
+            {
+                start_capture(context);
+            }
+        });
+
+        let profiler = &context.resources.profiler;
+        if let Some(stats) = query_frame_time_stats(profiler) {
+            ui.label(format!(
+                "Frame time: min {:.2} ms, avg {:.2} ms, p99 {:.2} ms",
+                stats.min, stats.average, stats.p99
+            ));
+        }
+        if let Some(draw_stats) = profiler.draw_stats.back() {
+            ui.label(format!(
+                "Draw calls: {}, instances: {}",
+                draw_stats.draw_calls, draw_stats.instances
+            ));
+        }
+
+        frame_time_graph_ui(ui, &profiler.frame_times);
+        ui.separator();
+
+        let frame_duration = profiler
+            .last_frame
+            .iter()
+            .map(|timing| timing.duration)
+            .sum::<f64>()
+            .max(f64::EPSILON);
+        egui::Grid::new(ui.id().with("system_timings"))
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for timing in &profiler.last_frame {
+                    ui.label(timing.name);
+                    ui.label(format!("{:.3} ms", timing.duration / 1000.0));
+                    ui.add(
+                        egui::ProgressBar::new((timing.duration / frame_duration) as f32)
+                            .desired_width(120.0),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Draws the frame time history as a line graph with 60 and 30 fps guides
+fn frame_time_graph_ui(ui: &mut egui::Ui, frame_times: &std::collections::VecDeque<f32>) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 80.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(24));
+
+    let max_time = frame_times.iter().copied().fold(33.3_f32, f32::max);
+    let y_for = |time: f32| rect.bottom() - rect.height() * (time / max_time);
+    for (time, label) in [(16.7, "60 fps"), (33.3, "30 fps")] {
+        let y = y_for(time);
+        painter.hline(
+            rect.x_range(),
+            y,
+            egui::Stroke::new(1.0, egui::Color32::from_gray(70)),
+        );
+        painter.text(
+            egui::pos2(rect.left() + 4.0, y),
+            egui::Align2::LEFT_BOTTOM,
+            label,
+            egui::FontId::proportional(10.0),
+            egui::Color32::from_gray(120),
+        );
+    }
+
+    let step = rect.width() / crate::context::profiler::HISTORY_LENGTH.max(2) as f32;
+    let points = frame_times
+        .iter()
+        .enumerate()
+        .map(|(index, time)| egui::pos2(rect.left() + index as f32 * step, y_for(*time)))
+        .collect::<Vec<_>>();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(130, 255, 170)),
+    ));
+}
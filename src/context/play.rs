@@ -0,0 +1,141 @@
+use crate::context::{
+    component_mask, get_component, get_component_mut,
+    graphics::RenderMode,
+    query_entity_components, remove_components, restore_world, set_entity_components,
+    snapshot_world, spawn_entity_with_components,
+    tree::Parent,
+    ui::{Pane, PaneKind},
+    Context, EntityId, WorldSnapshot, PARENT,
+};
+
+/// A resource separating edit-time state from play-time state
+#[derive(Default)]
+pub struct PlaySession {
+    /// The world as it was when run mode was entered
+    pub snapshot: Option<WorldSnapshot>,
+
+    /// Entities whose run mode changes survive returning to edit mode
+    pub keep_changes: std::collections::HashSet<EntityId>,
+}
+
+/// Snapshots the world when entering run mode and restores it when leaving,
+/// scheduled to run only when the render mode changes
+pub fn play_session_system(context: &mut Context) {
+    match context.resources.graphics.render_mode {
+        RenderMode::Run => {
+            context.resources.play_session.snapshot = Some(snapshot_world(context));
+        }
+        RenderMode::Edit => {
+            let Some(snapshot) = context.resources.play_session.snapshot.take() else {
+                return;
+            };
+            let keep_changes = std::mem::take(&mut context.resources.play_session.keep_changes);
+            let kept_entities = keep_changes
+                .into_iter()
+                .filter_map(|entity| Some((entity, query_entity_components(context, entity)?)))
+                .collect::<Vec<_>>();
+
+            restore_world(context, snapshot);
+
+            // Entities spawned during run mode don't exist in the snapshot
+            // and are spawned again under new ids
+            let mut entity_map = std::collections::HashMap::new();
+            for (entity, components) in kept_entities {
+                let restored_entity = if component_mask(context, entity).is_some() {
+                    set_entity_components(context, entity, components);
+                    entity
+                } else {
+                    spawn_entity_with_components(context, components)
+                };
+                entity_map.insert(entity, restored_entity);
+            }
+            remap_entity_references(context, &entity_map);
+            log::info!("Restored the world from before run mode");
+        }
+    }
+}
+
+/// Rewrites every stored entity reference through a map of respawned entities,
+/// clearing references to entities that no longer exist
+pub fn remap_entity_references(
+    context: &mut Context,
+    entity_map: &std::collections::HashMap<EntityId, EntityId>,
+) {
+    let remap = |context: &Context, entity: EntityId| {
+        let entity = entity_map.get(&entity).copied().unwrap_or(entity);
+        component_mask(context, entity).map(|_| entity)
+    };
+
+    // Only kept entities can refer to entities spawned during run mode
+    for entity in entity_map.values().copied() {
+        let Some(Parent(parent)) = get_component::<Parent>(context, entity, PARENT).copied() else {
+            continue;
+        };
+        match remap(context, parent) {
+            Some(parent) => {
+                if let Some(parent_component) = get_component_mut::<Parent>(context, entity, PARENT)
+                {
+                    *parent_component = Parent(parent);
+                }
+            }
+            None => {
+                remove_components(context, entity, PARENT);
+            }
+        }
+    }
+
+    let active_camera_entity = context
+        .resources
+        .active_camera_entity
+        .and_then(|entity| remap(context, entity));
+    context.resources.active_camera_entity = active_camera_entity;
+
+    let path_entity = context
+        .resources
+        .sequencer
+        .path_entity
+        .and_then(|entity| remap(context, entity));
+    let sequencer = &mut context.resources.sequencer;
+    sequencer.path_entity = path_entity;
+    sequencer.playing &= path_entity.is_some();
+
+    let user_interface = &context.resources.user_interface;
+    let selected_entity = user_interface
+        .selected_entity
+        .and_then(|entity| remap(context, entity));
+    let multi_selection = user_interface
+        .multi_selection
+        .iter()
+        .filter_map(|entity| remap(context, *entity))
+        .collect::<Vec<_>>();
+    let user_interface = &mut context.resources.user_interface;
+    user_interface.selected_entity = selected_entity;
+    user_interface.multi_selection = multi_selection;
+
+    // Scene panes are taken out so their references can be checked against the world
+    if let Some(mut tile_tree) = context.resources.user_interface.tile_tree.take() {
+        for (_, tile) in tile_tree.tiles.iter_mut() {
+            let egui_tiles::Tile::Pane(Pane {
+                kind:
+                    PaneKind::Scene {
+                        scene_entity,
+                        camera_entity,
+                    },
+                ..
+            }) = tile
+            else {
+                continue;
+            };
+            if let Some(new_scene) = entity_map.get(scene_entity) {
+                *scene_entity = *new_scene;
+            }
+            *camera_entity = camera_entity.and_then(|entity| remap(context, entity));
+        }
+        context.resources.user_interface.tile_tree = Some(tile_tree);
+    }
+
+    // Interpolation is keyed by entity, and the recorded ticks are from run mode
+    let time = &mut context.resources.time;
+    time.previous_global_transforms.clear();
+    time.current_global_transforms.clear();
+}
@@ -0,0 +1,197 @@
+use crate::context::{graphics::DrawStats, Context};
+
+/// The number of frames kept in the rolling history
+pub const HISTORY_LENGTH: usize = 300;
+
+/// A resource for CPU timing of systems and frames
+#[derive(Default)]
+pub struct Profiler {
+    /// The instant trace timestamps are measured from
+    pub origin: Option<std::time::Instant>,
+
+    /// When the current frame's systems started running
+    pub frame_start: Option<std::time::Instant>,
+
+    /// System timings recorded so far in the current frame
+    pub current_frame: Vec<SystemTiming>,
+
+    /// System timings of the last completed frame
+    pub last_frame: Vec<SystemTiming>,
+
+    /// Frame times in milliseconds, oldest first
+    pub frame_times: std::collections::VecDeque<f32>,
+
+    /// Renderer statistics for the frames in `frame_times`
+    pub draw_stats: std::collections::VecDeque<DrawStats>,
+
+    /// Stops recording history so the graphs can be inspected
+    pub paused: bool,
+
+    /// Trace events recorded while a capture is in progress
+    pub capture: Option<Vec<TraceEvent>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemTiming {
+    pub name: &'static str,
+
+    /// Microseconds since the profiler origin
+    pub start: f64,
+
+    /// Microseconds the system ran for
+    pub duration: f64,
+
+    /// The trace thread the system ran on, zero for the main thread
+    pub thread: u32,
+}
+
+/// A complete event in the Chrome trace event format
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TraceEvent {
+    pub name: String,
+    #[serde(rename = "cat")]
+    pub category: &'static str,
+    #[serde(rename = "ph")]
+    pub phase: &'static str,
+    #[serde(rename = "ts")]
+    pub timestamp: f64,
+    #[serde(rename = "dur")]
+    pub duration: f64,
+    pub pid: u32,
+    pub tid: u32,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FrameTimeStats {
+    pub min: f32,
+    pub average: f32,
+    pub p99: f32,
+}
+
+/// Marks the start of a frame, before any profiled system runs
+pub fn begin_frame_system(context: &mut Context) {
+    let profiler = &mut context.resources.profiler;
+    let now = std::time::Instant::now();
+    profiler.origin.get_or_insert(now);
+    profiler.frame_start = Some(now);
+    profiler.current_frame.clear();
+}
+
+/// Runs a system and records how long it took
+pub fn profile_system(
+    context: &mut Context,
+    name: &'static str,
+    system: impl FnOnce(&mut Context),
+) {
+    let start = std::time::Instant::now();
+    system(context);
+    let end = std::time::Instant::now();
+    record_system_timing(&mut context.resources.profiler, name, 0, start, end);
+}
+
+/// Records a system that ran between two instants on a thread
+pub fn record_system_timing(
+    profiler: &mut Profiler,
+    name: &'static str,
+    thread: u32,
+    start: std::time::Instant,
+    end: std::time::Instant,
+) {
+    let origin = *profiler.origin.get_or_insert(start);
+    profiler.current_frame.push(SystemTiming {
+        name,
+        start: (start - origin).as_secs_f64() * 1_000_000.0,
+        duration: (end - start).as_secs_f64() * 1_000_000.0,
+        thread,
+    });
+}
+
+/// Moves the frame's timings into the history and any running capture
+pub fn end_frame_system(context: &mut Context) {
+    let delta_time = context.resources.window.delta_time;
+    let draw_stats = context.resources.graphics.draw_stats;
+    let profiler = &mut context.resources.profiler;
+    let (Some(origin), Some(frame_start)) = (profiler.origin, profiler.frame_start.take()) else {
+        return;
+    };
+
+    profiler.last_frame = std::mem::take(&mut profiler.current_frame);
+
+    if let Some(capture) = profiler.capture.as_mut() {
+        capture.push(TraceEvent {
+            name: "frame".to_string(),
+            category: "frame",
+            phase: "X",
+            timestamp: (frame_start - origin).as_secs_f64() * 1_000_000.0,
+            duration: frame_start.elapsed().as_secs_f64() * 1_000_000.0,
+            pid: 1,
+            tid: 1,
+        });
+        capture.extend(profiler.last_frame.iter().map(|timing| TraceEvent {
+            name: timing.name.to_string(),
+            category: "system",
+            phase: "X",
+            timestamp: timing.start,
+            duration: timing.duration,
+            pid: 1,
+            tid: timing.thread + 1,
+        }));
+    }
+
+    // The first frame has no previous frame to measure against
+    if profiler.paused || delta_time <= 0.0 {
+        return;
+    }
+    if profiler.frame_times.len() == HISTORY_LENGTH {
+        profiler.frame_times.pop_front();
+        profiler.draw_stats.pop_front();
+    }
+    profiler.frame_times.push_back(delta_time * 1000.0);
+    profiler.draw_stats.push_back(draw_stats);
+}
+
+/// Query for the min, average and 99th percentile of the recent frame times
+pub fn query_frame_time_stats(profiler: &Profiler) -> Option<FrameTimeStats> {
+    if profiler.frame_times.is_empty() {
+        return None;
+    }
+    let mut frame_times = profiler.frame_times.iter().copied().collect::<Vec<_>>();
+    frame_times.sort_by(f32::total_cmp);
+    let p99_index = ((frame_times.len() as f32 * 0.99).ceil() as usize).saturating_sub(1);
+    Some(FrameTimeStats {
+        min: frame_times[0],
+        average: frame_times.iter().sum::<f32>() / frame_times.len() as f32,
+        p99: frame_times[p99_index.min(frame_times.len() - 1)],
+    })
+}
+
+/// Starts recording trace events, discarding any previous capture
+pub fn start_capture(context: &mut Context) {
+    context.resources.profiler.capture = Some(Vec::new());
+}
+
+/// Stops recording and writes the capture as a Chrome trace JSON file
+pub fn stop_capture(context: &mut Context) -> Option<std::path::PathBuf> {
+    let events = context.resources.profiler.capture.take()?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = std::path::PathBuf::from(format!("trace-{timestamp}.json"));
+    let contents = match serde_json::to_string(&serde_json::json!({ "traceEvents": events })) {
+        Ok(contents) => contents,
+        Err(error) => {
+            log::error!("Failed to serialize profiler capture: {error}");
+            return None;
+        }
+    };
+    if let Err(error) = std::fs::write(&path, contents) {
+        log::error!(
+            "Failed to write profiler capture {}: {error}",
+            path.display()
+        );
+        return None;
+    }
+    log::info!("Wrote {} trace events to {}", events.len(), path.display());
+    Some(path)
+}
@@ -0,0 +1,401 @@
+use crate::context::{
+    actions::ActionMap,
+    config::{capture_editor_config, EditorConfig},
+    graphics::RenderMode,
+    input::{apply_input_event, InputEvent},
+    play::remap_entity_references,
+    restore_world, snapshot_world,
+    ui::{apply_editor_config, select_tile},
+    Context, EntityId, WorldSnapshot,
+};
+
+/// A resource that records the input of a session or replays a recording.
+/// Recordings hold the world, active camera, input bindings and editor config when recording
+/// started, then the input state, the editor gui's input, frame timing, render mode
+/// and cursor grab of every frame. Other resources are not part of them.
+#[derive(Default)]
+pub enum Replay {
+    #[default]
+    Idle,
+    Recording {
+        path: std::path::PathBuf,
+        recording: InputRecording,
+        /// Input events received since the last recorded frame
+        pending_events: Vec<InputEvent>,
+        /// The pane selected by a click outside the gui since the last recorded frame
+        pending_selected_tile: Option<Option<egui_tiles::TileId>>,
+        /// The render mode and cursor grab of the last recorded frame
+        render_mode: RenderMode,
+        cursor_grabbed: bool,
+    },
+    Playing {
+        recording: InputRecording,
+        frame: usize,
+        /// The gui input of the frame being replayed, used in place of the window's
+        gui_input: Option<egui::RawInput>,
+    },
+}
+
+impl Replay {
+    pub fn is_playing(&self) -> bool {
+        matches!(self, Self::Playing { .. })
+    }
+}
+
+/// Recordings are written with bincode, a compact binary encoding
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct InputRecording {
+    /// The world as it was on the first recorded frame, restored before replaying
+    pub world: Option<WorldSnapshot>,
+    pub active_camera_entity: Option<EntityId>,
+    /// The input bindings the recording was made with, used instead of the replaying user's
+    pub action_map: ActionMap,
+    /// The editor config the recording was made with, whose layout recorded panes refer to
+    #[serde(with = "json_text")]
+    pub editor_config: EditorConfig,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Encodes a value as JSON text inside a recording.
+/// Panel states are free-form JSON values, which bincode can only decode as text.
+mod json_text {
+    pub fn serialize<T: serde::Serialize, S: serde::Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let text = serde_json::to_string(value).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, T: serde::de::DeserializeOwned, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+        serde_json::from_str(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The input received before a frame and the frame's delta time
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedFrame {
+    pub delta_time: f32,
+
+    pub events: Vec<InputEvent>,
+
+    /// The render mode, when it changed since the previous frame
+    pub render_mode: Option<RenderMode>,
+
+    /// Whether the cursor is grabbed, when it changed since the previous frame
+    pub cursor_grabbed: Option<bool>,
+
+    /// The input the editor gui took this frame
+    pub gui_input: Option<egui::RawInput>,
+
+    /// The pane selected by a click outside the gui, when one was clicked
+    pub selected_tile: Option<Option<egui_tiles::TileId>>,
+}
+
+/// Starts recording input, written to the path when the session ends
+pub fn start_recording(context: &mut Context, path: std::path::PathBuf) {
+    log::info!("Recording input to {}", path.display());
+    context.resources.replay = Replay::Recording {
+        path,
+        recording: InputRecording::default(),
+        pending_events: Vec::new(),
+        pending_selected_tile: None,
+        render_mode: RenderMode::default(),
+        cursor_grabbed: false,
+    };
+}
+
+/// Loads a recording to replay from the next frame, with the bindings and editor config
+/// it was made with. Live input is ignored until it ends.
+pub fn start_replay(context: &mut Context, path: &std::path::Path) -> bool {
+    let recording = match std::fs::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            bincode::deserialize::<InputRecording>(&contents).map_err(|error| error.to_string())
+        }) {
+        Ok(recording) => recording,
+        Err(error) => {
+            log::error!("Failed to load input recording {}: {error}", path.display());
+            return false;
+        }
+    };
+    log::info!(
+        "Replaying {} frames of input from {}",
+        recording.frames.len(),
+        path.display()
+    );
+    context.resources.action_map = recording.action_map.clone();
+    context.resources.editor_config = recording.editor_config.clone();
+    context.resources.replay = Replay::Playing {
+        recording,
+        frame: 0,
+        gui_input: None,
+    };
+    true
+}
+
+/// Buffers an input event for the next recorded frame
+pub fn record_input_event(context: &mut Context, input_event: InputEvent) {
+    if let Replay::Recording { pending_events, .. } = &mut context.resources.replay {
+        pending_events.push(input_event);
+    }
+}
+
+/// Buffers the selection of a pane clicked outside the gui for the next recorded frame
+pub fn record_tile_selection(context: &mut Context, tile_id: Option<egui_tiles::TileId>) {
+    if let Replay::Recording {
+        pending_selected_tile,
+        ..
+    } = &mut context.resources.replay
+    {
+        *pending_selected_tile = Some(tile_id);
+    }
+}
+
+/// Records the input the gui took this frame when recording,
+/// or replaces it with the recorded frame's when replaying
+pub fn replay_gui_input(context: &mut Context, gui_input: egui::RawInput) -> egui::RawInput {
+    match &mut context.resources.replay {
+        Replay::Idle => gui_input,
+        Replay::Recording { recording, .. } => {
+            if let Some(recorded_frame) = recording.frames.last_mut() {
+                recorded_frame.gui_input = Some(gui_input.clone());
+            }
+            gui_input
+        }
+        Replay::Playing {
+            gui_input: recorded_gui_input,
+            ..
+        } => recorded_gui_input.take().unwrap_or_default(),
+    }
+}
+
+/// Records the frame's input and delta time, or replaces them with the next recorded frame.
+/// Runs after frame timing and before any system that reads input.
+pub fn replay_system(context: &mut Context) {
+    let render_mode = context.resources.graphics.render_mode;
+    let cursor_grabbed = context.resources.window.cursor_grabbed;
+    let is_first_frame = matches!(
+        &context.resources.replay,
+        Replay::Recording { recording, .. } if recording.world.is_none()
+    );
+    if is_first_frame {
+        capture_editor_config(context);
+    }
+    let world = is_first_frame.then(|| snapshot_world(context));
+    let active_camera_entity = context.resources.active_camera_entity;
+    let action_map = &context.resources.action_map;
+    let editor_config = &context.resources.editor_config;
+    match &mut context.resources.replay {
+        Replay::Idle => {}
+        Replay::Recording {
+            recording,
+            pending_events,
+            pending_selected_tile,
+            render_mode: previous_render_mode,
+            cursor_grabbed: previous_cursor_grabbed,
+            ..
+        } => {
+            if world.is_some() {
+                recording.world = world;
+                recording.active_camera_entity = active_camera_entity;
+                recording.action_map = action_map.clone();
+                recording.editor_config = editor_config.clone();
+            }
+            recording.frames.push(RecordedFrame {
+                delta_time: context.resources.window.delta_time,
+                events: std::mem::take(pending_events),
+                render_mode: (render_mode != *previous_render_mode).then_some(render_mode),
+                cursor_grabbed: (cursor_grabbed != *previous_cursor_grabbed)
+                    .then_some(cursor_grabbed),
+                gui_input: None,
+                selected_tile: pending_selected_tile.take(),
+            });
+            *previous_render_mode = render_mode;
+            *previous_cursor_grabbed = cursor_grabbed;
+        }
+        Replay::Playing {
+            recording,
+            frame,
+            gui_input,
+        } => {
+            let Some(mut recorded_frame) = recording.frames.get(*frame).cloned() else {
+                log::info!("Finished replaying input");
+                context.resources.replay = Replay::Idle;
+                return;
+            };
+            let world = (*frame == 0).then(|| recording.world.take()).flatten();
+            *frame += 1;
+            *gui_input = recorded_frame.gui_input.take();
+            let active_camera_entity = recording.active_camera_entity;
+            if let Some(world) = world {
+                restore_world(context, world);
+                context.resources.active_camera_entity = active_camera_entity;
+                apply_editor_config(context);
+                // Other resources are not recorded, so references to entities
+                // that are not in the recorded world are cleared
+                remap_entity_references(context, &std::collections::HashMap::new());
+            }
+            context.resources.window.delta_time = recorded_frame.delta_time;
+            if let Some(render_mode) = recorded_frame.render_mode {
+                context.resources.graphics.render_mode = render_mode;
+            }
+            if let Some(cursor_grabbed) = recorded_frame.cursor_grabbed {
+                context.resources.window.cursor_grabbed = cursor_grabbed;
+            }
+            for input_event in recorded_frame.events {
+                apply_input_event(context, input_event);
+            }
+            if let Some(tile_id) = recorded_frame.selected_tile {
+                select_tile(context, tile_id);
+            }
+        }
+    }
+}
+
+/// Writes the recording to disk when recording
+pub fn save_recording_system(context: &mut Context) {
+    let Replay::Recording {
+        path, recording, ..
+    } = &context.resources.replay
+    else {
+        return;
+    };
+    let contents = match bincode::serialize(recording) {
+        Ok(contents) => contents,
+        Err(error) => {
+            log::error!("Failed to serialize input recording: {error}");
+            return;
+        }
+    };
+    match std::fs::write(path, contents) {
+        Ok(()) => log::info!(
+            "Saved {} frames of input to {}",
+            recording.frames.len(),
+            path.display()
+        ),
+        Err(error) => {
+            log::error!(
+                "Failed to write input recording {}: {error}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{
+        actions::BOOST,
+        input::{reset_input_system, MouseState},
+        query_entities, spawn_entities,
+        ui::{Pane, PaneKind},
+        LOCAL_TRANSFORM,
+    };
+    use winit::{event::MouseButton, keyboard::KeyCode};
+
+    fn run_frame(context: &mut Context, delta_time: f32) -> (bool, bool, MouseState, f32) {
+        context.resources.window.delta_time = delta_time;
+        replay_system(context);
+        let input = &context.resources.input;
+        let observed = (
+            input.keyboard.is_key_just_pressed(KeyCode::KeyW),
+            input.keyboard.is_key_pressed(KeyCode::KeyW),
+            input.mouse.double_clicked,
+            input.mouse.wheel_delta.y,
+        );
+        reset_input_system(context);
+        observed
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        let frames = [
+            vec![InputEvent::Key {
+                key_code: KeyCode::KeyW,
+                pressed: true,
+                repeat: false,
+            }],
+            vec![
+                InputEvent::MouseButton {
+                    button: MouseButton::Left,
+                    pressed: true,
+                },
+                InputEvent::MouseButton {
+                    button: MouseButton::Left,
+                    pressed: false,
+                },
+            ],
+            vec![
+                InputEvent::MouseButton {
+                    button: MouseButton::Left,
+                    pressed: true,
+                },
+                InputEvent::Scrolled(nalgebra_glm::vec2(0.0, 1.5)),
+            ],
+            vec![InputEvent::Key {
+                key_code: KeyCode::KeyW,
+                pressed: false,
+                repeat: false,
+            }],
+        ];
+
+        let mut recorded_context = Context::default();
+        let entity = spawn_entities(&mut recorded_context, LOCAL_TRANSFORM, 1)[0];
+        recorded_context.resources.action_map.bindings.remove(BOOST);
+        recorded_context.resources.user_interface.tile_tree = Some(egui_tiles::Tree::new_tabs(
+            "layout",
+            vec![Pane {
+                kind: PaneKind::Panel("Console".to_string()),
+                state: serde_json::json!({ "filter": "warn" }),
+            }],
+        ));
+        start_recording(&mut recorded_context, std::path::PathBuf::new());
+        let mut recorded = Vec::new();
+        for events in &frames {
+            for event in events {
+                record_input_event(&mut recorded_context, *event);
+                apply_input_event(&mut recorded_context, *event);
+            }
+            recorded.push(run_frame(&mut recorded_context, 0.1));
+        }
+        let Replay::Recording { recording, .. } = &recorded_context.resources.replay else {
+            panic!("Expected to be recording");
+        };
+        let path = std::env::temp_dir().join(format!("replay_{}.bin", std::process::id()));
+        std::fs::write(&path, bincode::serialize(recording).unwrap()).unwrap();
+
+        let mut replayed_context = Context::default();
+        assert!(start_replay(&mut replayed_context, &path));
+        std::fs::remove_file(&path).unwrap();
+        assert!(!start_replay(&mut Context::default(), &path));
+        assert!(!replayed_context
+            .resources
+            .action_map
+            .bindings
+            .contains_key(BOOST));
+        assert_eq!(
+            serde_json::to_value(&replayed_context.resources.editor_config.tile_tree).unwrap(),
+            serde_json::to_value(&recorded_context.resources.user_interface.tile_tree).unwrap()
+        );
+        let replayed = frames
+            .iter()
+            .map(|_| run_frame(&mut replayed_context, 1.0))
+            .collect::<Vec<_>>();
+
+        assert_eq!(recorded, replayed);
+        assert_eq!(
+            query_entities(&replayed_context, LOCAL_TRANSFORM),
+            vec![entity]
+        );
+        assert_eq!(recorded[2].2, MouseState::LEFT_CLICKED);
+        assert_eq!(recorded[2].3, 1.5);
+
+        run_frame(&mut replayed_context, 1.0);
+        assert!(!replayed_context.resources.replay.is_playing());
+    }
+}
@@ -0,0 +1,565 @@
+use crate::context::{
+    graphics::RenderMode,
+    profiler::profile_system,
+    time::{accumulate_fixed_ticks, begin_fixed_tick, end_fixed_tick},
+    Context, COMPONENT_COUNT,
+};
+
+/// The stages of a frame, run in declaration order
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    /// Runs once, before the first frame
+    Startup,
+    PreUpdate,
+    /// Runs zero or more times per frame at the fixed tick rate of the `Time` resource
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    Render,
+    Cleanup,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 7] = [
+        Stage::Startup,
+        Stage::PreUpdate,
+        Stage::FixedUpdate,
+        Stage::Update,
+        Stage::PostUpdate,
+        Stage::Render,
+        Stage::Cleanup,
+    ];
+}
+
+pub type RunCondition = Box<dyn FnMut(&Context) -> bool + Send>;
+
+bitflags::bitflags! {
+    /// The resource fields a system can declare access to
+    #[derive(Default, Debug, Clone, Copy, PartialEq)]
+    pub struct ResourceMask: u32 {
+        const WINDOW = 1 << 0;
+        const GRAPHICS = 1 << 1;
+        const INPUT = 1 << 2;
+        const ACTION_MAP = 1 << 3;
+        const ACTIVE_CAMERA_ENTITY = 1 << 4;
+        const FRAME_CAPTURE = 1 << 5;
+        const REPLAY = 1 << 6;
+        const SEQUENCER = 1 << 7;
+        const TIME = 1 << 8;
+    }
+}
+
+/// The components and resources a system reads and writes.
+/// Systems with no declared access are exclusive and always batched alone.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Access {
+    /// Component mask of the components read
+    pub reads: u32,
+    /// Component mask of the components written
+    pub writes: u32,
+    /// The resource fields read
+    pub resource_reads: ResourceMask,
+    /// The resource fields written
+    pub resource_writes: ResourceMask,
+}
+
+impl Access {
+    /// Whether one system writes something the other reads or writes
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        let writes_components = |a: &Access, b: &Access| a.writes & (b.reads | b.writes) != 0;
+        let writes_resources = |a: &Access, b: &Access| {
+            a.resource_writes
+                .intersects(b.resource_reads | b.resource_writes)
+        };
+        writes_components(self, other)
+            || writes_components(other, self)
+            || writes_resources(self, other)
+            || writes_resources(other, self)
+    }
+}
+
+/// A system function with its stage, ordering constraints and run conditions
+pub struct System {
+    pub name: &'static str,
+    pub stage: Stage,
+    pub run: Box<dyn Fn(&mut Context) + Send + Sync>,
+    /// Systems in the same stage that must run after this one
+    pub before: Vec<&'static str>,
+    /// Systems in the same stage that must run before this one
+    pub after: Vec<&'static str>,
+    /// The system is skipped unless every condition holds
+    pub run_conditions: Vec<RunCondition>,
+    /// The declared access, or `None` for an exclusive system
+    pub access: Option<Access>,
+    /// The system never shares a batch, so it stays on the main thread
+    /// once batches run concurrently
+    pub main_thread: bool,
+}
+
+impl System {
+    /// Wraps a system function, named after the function, in the `Update` stage.
+    /// Closures have no usable name and must be wrapped with `System::named` instead.
+    pub fn new<F: Fn(&mut Context) + Send + Sync + 'static>(system: F) -> Self {
+        Self::named(system_name::<F>(), system)
+    }
+
+    /// Wraps a system under an explicit name, in the `Update` stage.
+    /// Ordering constraints and the profiler refer to systems by name,
+    /// so names must be unique within a schedule.
+    pub fn named<F: Fn(&mut Context) + Send + Sync + 'static>(
+        name: &'static str,
+        system: F,
+    ) -> Self {
+        Self {
+            name,
+            stage: Stage::Update,
+            run: Box::new(system),
+            before: Vec::new(),
+            after: Vec::new(),
+            run_conditions: Vec::new(),
+            access: None,
+            main_thread: false,
+        }
+    }
+
+    pub fn in_stage(mut self, stage: Stage) -> Self {
+        self.stage = stage;
+        self
+    }
+
+    pub fn before<F: Fn(&mut Context)>(mut self, _system: F) -> Self {
+        self.before.push(system_name::<F>());
+        self
+    }
+
+    pub fn after<F: Fn(&mut Context)>(mut self, _system: F) -> Self {
+        self.after.push(system_name::<F>());
+        self
+    }
+
+    pub fn run_if(mut self, condition: impl FnMut(&Context) -> bool + Send + 'static) -> Self {
+        self.run_conditions.push(Box::new(condition));
+        self
+    }
+
+    /// Declares the components the system reads, allowing it to share a batch
+    pub fn reads(mut self, components: u32) -> Self {
+        self.access.get_or_insert_with(Access::default).reads |= components;
+        self
+    }
+
+    /// Declares the components the system writes, allowing it to share a batch
+    pub fn writes(mut self, components: u32) -> Self {
+        self.access.get_or_insert_with(Access::default).writes |= components;
+        self
+    }
+
+    /// Declares the resource fields the system reads, allowing it to share a batch
+    pub fn reads_resource(mut self, resources: ResourceMask) -> Self {
+        self.access
+            .get_or_insert_with(Access::default)
+            .resource_reads |= resources;
+        self
+    }
+
+    /// Declares the resource fields the system writes, allowing it to share a batch
+    pub fn writes_resource(mut self, resources: ResourceMask) -> Self {
+        self.access
+            .get_or_insert_with(Access::default)
+            .resource_writes |= resources;
+        self
+    }
+
+    /// Keeps the system out of shared batches even when it declares its access.
+    /// Systems that touch the window, renderer or gui need this, as those are main thread only.
+    pub fn on_main_thread(mut self) -> Self {
+        self.main_thread = true;
+        self
+    }
+
+    /// Whether an ordering constraint exists between two systems
+    fn is_ordered_with(&self, other: &System) -> bool {
+        self.before.contains(&other.name)
+            || self.after.contains(&other.name)
+            || other.before.contains(&self.name)
+            || other.after.contains(&self.name)
+    }
+
+    /// Whether the system may share a batch with another system
+    fn can_run_alongside(&self, other: &System) -> bool {
+        if self.main_thread || other.main_thread {
+            return false;
+        }
+        match (&self.access, &other.access) {
+            (Some(access), Some(other_access)) => {
+                !access.conflicts_with(other_access) && !self.is_ordered_with(other)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The last path segment of a function's type name, e.g. `render_frame_system`.
+/// Panics for closures, which would all share the name `{{closure}}`.
+pub fn system_name<F>() -> &'static str {
+    let type_name = std::any::type_name::<F>();
+    let name = type_name.rsplit("::").next().unwrap_or(type_name);
+    assert!(
+        name != "{{closure}}",
+        "Closure systems must be named explicitly with `System::named`: {type_name}"
+    );
+    name
+}
+
+/// A resource holding the systems run each frame
+#[derive(Default)]
+pub struct Schedule {
+    pub systems: Vec<System>,
+
+    /// Batches of indices into `systems` for each stage in execution order,
+    /// rebuilt when systems are added. Systems in a batch have no conflicting access.
+    order: Option<Vec<(Stage, Vec<Vec<usize>>)>>,
+
+    startup_complete: bool,
+}
+
+impl Schedule {
+    /// Adds a system, panicking if the schedule already has a system with the same name
+    /// or the system declares access to components that do not exist
+    pub fn add_system(&mut self, system: System) {
+        assert!(
+            self.systems.iter().all(|other| other.name != system.name),
+            "A system named `{}` is already in the schedule",
+            system.name
+        );
+        if let Some(access) = &system.access {
+            let components = (1_u32 << COMPONENT_COUNT) - 1;
+            assert!(
+                (access.reads | access.writes) & !components == 0,
+                "The system `{}` declares access to unknown components",
+                system.name
+            );
+        }
+        self.systems.push(system);
+        self.order = None;
+    }
+
+    pub fn add_systems(&mut self, systems: impl IntoIterator<Item = System>) {
+        systems
+            .into_iter()
+            .for_each(|system| self.add_system(system));
+    }
+
+    /// Takes the batched order out of the schedule, building it first if systems changed.
+    /// It is returned with `restore_order` once the stages have run.
+    fn take_order(&mut self) -> Vec<(Stage, Vec<Vec<usize>>)> {
+        let systems = &self.systems;
+        self.order.take().unwrap_or_else(|| {
+            Stage::ALL
+                .into_iter()
+                .map(|stage| (stage, batch_stage(systems, &sort_stage(systems, stage))))
+                .collect()
+        })
+    }
+
+    fn restore_order(&mut self, order: Vec<(Stage, Vec<Vec<usize>>)>) {
+        self.order = Some(order);
+    }
+}
+
+/// Orders a stage's systems so every before/after constraint holds,
+/// keeping registration order where the constraints allow
+fn sort_stage(systems: &[System], stage: Stage) -> Vec<usize> {
+    let indices = (0..systems.len())
+        .filter(|index| systems[*index].stage == stage)
+        .collect::<Vec<_>>();
+    let index_of = |name: &str| {
+        indices
+            .iter()
+            .copied()
+            .find(|index| systems[*index].name == name)
+    };
+
+    // Edges point from a system to the systems that must run after it
+    let mut dependents = std::collections::HashMap::<usize, Vec<usize>>::new();
+    let mut dependency_counts = indices
+        .iter()
+        .map(|index| (*index, 0))
+        .collect::<std::collections::HashMap<_, _>>();
+    for index in indices.iter().copied() {
+        let system = &systems[index];
+        let edges = system
+            .before
+            .iter()
+            .filter_map(|name| index_of(name).map(|other| (index, other)))
+            .chain(
+                system
+                    .after
+                    .iter()
+                    .filter_map(|name| index_of(name).map(|other| (other, index))),
+            );
+        for (first, second) in edges {
+            dependents.entry(first).or_default().push(second);
+            *dependency_counts.entry(second).or_default() += 1;
+        }
+    }
+
+    let mut sorted = Vec::with_capacity(indices.len());
+    let mut remaining = indices;
+    while !remaining.is_empty() {
+        let Some(position) = remaining
+            .iter()
+            .position(|index| dependency_counts[index] == 0)
+        else {
+            let names = remaining
+                .iter()
+                .map(|index| systems[*index].name)
+                .collect::<Vec<_>>();
+            log::error!("System ordering cycle in {stage:?} between {names:?}");
+            sorted.extend(remaining);
+            break;
+        };
+        let index = remaining.remove(position);
+        for dependent in dependents.get(&index).into_iter().flatten() {
+            if let Some(count) = dependency_counts.get_mut(dependent) {
+                *count -= 1;
+            }
+        }
+        sorted.push(index);
+    }
+    sorted
+}
+
+/// Groups a sorted stage into consecutive batches of systems that can run together.
+/// A system joins the previous batch only if it can run alongside every system in it,
+/// so conflicting or ordered systems keep their sorted order.
+fn batch_stage(systems: &[System], sorted: &[usize]) -> Vec<Vec<usize>> {
+    let mut batches: Vec<Vec<usize>> = Vec::new();
+    for index in sorted.iter().copied() {
+        match batches.last_mut() {
+            Some(batch)
+                if batch
+                    .iter()
+                    .all(|other| systems[index].can_run_alongside(&systems[*other])) =>
+            {
+                batch.push(index);
+            }
+            _ => batches.push(vec![index]),
+        }
+    }
+    batches
+}
+
+/// Runs every stage of the schedule once, running the startup stage only on the first call
+pub fn run_schedule_system(context: &mut Context) {
+    // Systems may register other systems, so the schedule is taken out while it runs
+    let mut schedule = std::mem::take(&mut context.resources.schedule);
+    let order = schedule.take_order();
+    for (stage, batches) in &order {
+        if *stage == Stage::Startup && schedule.startup_complete {
+            continue;
+        }
+        if *stage == Stage::FixedUpdate {
+            for _ in 0..accumulate_fixed_ticks(context) {
+                begin_fixed_tick(context);
+                run_stage(context, &mut schedule, batches);
+                end_fixed_tick(context);
+            }
+        } else {
+            run_stage(context, &mut schedule, batches);
+        }
+    }
+    schedule.restore_order(order);
+    schedule.startup_complete = true;
+
+    let added_systems = std::mem::take(&mut context.resources.schedule.systems);
+    schedule.add_systems(added_systems);
+    context.resources.schedule = schedule;
+}
+
+fn run_stage(context: &mut Context, schedule: &mut Schedule, batches: &[Vec<usize>]) {
+    for batch in batches {
+        let runnable = batch
+            .iter()
+            .copied()
+            .filter(|index| {
+                schedule.systems[*index]
+                    .run_conditions
+                    .iter_mut()
+                    .all(|condition| condition(context))
+            })
+            .collect::<Vec<_>>();
+        run_batch(context, schedule, &runnable);
+    }
+}
+
+/// Runs a batch of systems in order on the main thread.
+/// Batches stay sequential until systems can be given disjoint borrows of the context
+/// built from their declared access, since every system takes the whole `&mut Context`.
+fn run_batch(context: &mut Context, schedule: &Schedule, batch: &[usize]) {
+    for index in batch {
+        let system = &schedule.systems[*index];
+        profile_system(context, system.name, &system.run);
+    }
+}
+
+/// A run condition that holds once the renderer is initialized,
+/// which it never is when replaying headless
+pub fn has_renderer(context: &Context) -> bool {
+    context.resources.graphics.renderer.is_some()
+}
+
+/// A run condition that holds while the editor is in the given render mode
+pub fn in_render_mode(render_mode: RenderMode) -> impl FnMut(&Context) -> bool + Send {
+    move |context| context.resources.graphics.render_mode == render_mode
+}
+
+/// A run condition that holds on the first run and
+/// whenever the queried resource value differs from the previous run
+pub fn resource_changed<T: PartialEq + Send + 'static>(
+    query: fn(&Context) -> T,
+) -> impl FnMut(&Context) -> bool + Send {
+    let mut previous = None;
+    move |context| {
+        let value = query(context);
+        let changed = previous.as_ref() != Some(&value);
+        previous = Some(value);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{GLOBAL_TRANSFORM, LOCAL_TRANSFORM};
+
+    #[test]
+    fn access_conflicts() {
+        let reader = Access {
+            reads: LOCAL_TRANSFORM,
+            ..Default::default()
+        };
+        let writer = Access {
+            writes: LOCAL_TRANSFORM,
+            ..Default::default()
+        };
+        let other_writer = Access {
+            writes: GLOBAL_TRANSFORM,
+            resource_reads: ResourceMask::INPUT,
+            ..Default::default()
+        };
+        let resource_writer = Access {
+            resource_writes: ResourceMask::INPUT,
+            ..Default::default()
+        };
+        assert!(!reader.conflicts_with(&reader));
+        assert!(reader.conflicts_with(&writer));
+        assert!(writer.conflicts_with(&writer));
+        assert!(!writer.conflicts_with(&other_writer));
+        assert!(other_writer.conflicts_with(&resource_writer));
+        assert!(!reader.conflicts_with(&resource_writer));
+    }
+
+    #[test]
+    fn conflicting_systems_never_share_a_batch() {
+        fn first_writer(_context: &mut Context) {}
+        fn second_writer(_context: &mut Context) {}
+        fn reader(_context: &mut Context) {}
+        fn global_writer(_context: &mut Context) {}
+
+        let systems = [
+            System::new(first_writer).writes(LOCAL_TRANSFORM),
+            System::new(second_writer).writes(LOCAL_TRANSFORM),
+            System::new(reader).reads(LOCAL_TRANSFORM),
+            System::new(global_writer).writes(GLOBAL_TRANSFORM),
+        ];
+        assert_eq!(
+            batch_stage(&systems, &[0, 1, 2, 3]),
+            vec![vec![0], vec![1], vec![2, 3]]
+        );
+    }
+
+    #[test]
+    fn exclusive_and_ordered_systems_never_share_a_batch() {
+        fn exclusive(_context: &mut Context) {}
+        fn early(_context: &mut Context) {}
+        fn late(_context: &mut Context) {}
+
+        let systems = [
+            System::new(late).writes(LOCAL_TRANSFORM).after(early),
+            System::new(early).writes(GLOBAL_TRANSFORM),
+            System::new(exclusive),
+        ];
+        let sorted = sort_stage(&systems, Stage::Update);
+        assert_eq!(sorted, vec![1, 0, 2]);
+        assert_eq!(
+            batch_stage(&systems, &sorted),
+            vec![vec![1], vec![0], vec![2]]
+        );
+    }
+
+    #[test]
+    fn main_thread_systems_run_alone() {
+        fn window_system(_context: &mut Context) {}
+        fn input_system(_context: &mut Context) {}
+
+        let systems = [
+            System::new(window_system)
+                .writes_resource(ResourceMask::WINDOW)
+                .on_main_thread(),
+            System::new(input_system).reads_resource(ResourceMask::INPUT),
+        ];
+        assert_eq!(batch_stage(&systems, &[0, 1]), vec![vec![0], vec![1]]);
+        assert_eq!(batch_stage(&systems[1..], &[0, 0]), vec![vec![0, 0]]);
+    }
+
+    #[test]
+    fn systems_need_unique_names() {
+        fn named_system(_context: &mut Context) {}
+
+        let unnamed = std::panic::catch_unwind(|| System::new(|_context: &mut Context| {}));
+        assert!(unnamed.is_err());
+        let closure = System::named("closure_system", |_context: &mut Context| {});
+        assert_eq!(closure.name, "closure_system");
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems([System::new(named_system), closure]);
+        let duplicate = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            schedule.add_system(System::named("named_system", named_system));
+        }));
+        assert!(duplicate.is_err());
+        assert_eq!(schedule.systems.len(), 2);
+    }
+
+    #[test]
+    fn systems_declare_known_components() {
+        fn unknown_writer(_context: &mut Context) {}
+
+        let mut schedule = Schedule::default();
+        let unknown = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            schedule.add_system(System::new(unknown_writer).writes(1 << COMPONENT_COUNT));
+        }));
+        assert!(unknown.is_err());
+        assert!(schedule.systems.is_empty());
+    }
+
+    #[test]
+    fn systems_run_in_order() {
+        static ORDER: std::sync::Mutex<Vec<&str>> = std::sync::Mutex::new(Vec::new());
+        fn local_writer(_context: &mut Context) {
+            ORDER.lock().unwrap().push("local_writer");
+        }
+        fn global_writer(_context: &mut Context) {
+            ORDER.lock().unwrap().push("global_writer");
+        }
+
+        let mut context = Context::default();
+        context.resources.schedule.add_systems([
+            System::new(local_writer).writes(LOCAL_TRANSFORM),
+            System::new(global_writer).writes(GLOBAL_TRANSFORM),
+        ]);
+        run_schedule_system(&mut context);
+
+        assert_eq!(*ORDER.lock().unwrap(), ["local_writer", "global_writer"]);
+    }
+}
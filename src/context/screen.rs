@@ -0,0 +1,192 @@
+#![allow(dead_code)]
+
+use crate::context::{
+    camera::{query_pane_camera_matrices, CameraMatrices},
+    Context, EntityId,
+};
+
+/// A half line in world space
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: nalgebra_glm::Vec3,
+    /// Normalized
+    pub direction: nalgebra_glm::Vec3,
+}
+
+impl Ray {
+    pub fn at(&self, distance: f32) -> nalgebra_glm::Vec3 {
+        self.origin + self.direction * distance
+    }
+}
+
+/// Projects a world point into a camera's pane, returning its position in the pane's
+/// coordinate space and its normalized device depth from 0 at the near plane to 1 at the far
+/// plane, or `None` behind the camera
+pub fn world_to_screen(
+    context: &Context,
+    camera_entity: EntityId,
+    pane_rect: egui::Rect,
+    point: nalgebra_glm::Vec3,
+) -> Option<(egui::Pos2, f32)> {
+    let matrices = query_pane_camera_matrices(context, camera_entity, pane_rect)?;
+    project_point(&matrices, pane_rect, point)
+}
+
+/// Unprojects a position in a camera's pane at a normalized device depth into world space
+pub fn screen_to_world(
+    context: &Context,
+    camera_entity: EntityId,
+    pane_rect: egui::Rect,
+    position: egui::Pos2,
+    depth: f32,
+) -> Option<nalgebra_glm::Vec3> {
+    let matrices = query_pane_camera_matrices(context, camera_entity, pane_rect)?;
+    unproject_point(&matrices, pane_rect, position, depth)
+}
+
+/// The world ray through a position in a camera's pane, starting on the near plane
+pub fn screen_ray(
+    context: &Context,
+    camera_entity: EntityId,
+    pane_rect: egui::Rect,
+    position: egui::Pos2,
+) -> Option<Ray> {
+    let matrices = query_pane_camera_matrices(context, camera_entity, pane_rect)?;
+    pixel_ray(&matrices, pane_rect, position)
+}
+
+pub fn project_point(
+    matrices: &CameraMatrices,
+    pane_rect: egui::Rect,
+    point: nalgebra_glm::Vec3,
+) -> Option<(egui::Pos2, f32)> {
+    let clip =
+        matrices.projection * matrices.view * nalgebra_glm::vec4(point.x, point.y, point.z, 1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let ndc = clip.xyz() / clip.w;
+    let position = egui::pos2(
+        pane_rect.min.x + (ndc.x + 1.0) * 0.5 * pane_rect.width(),
+        pane_rect.min.y + (1.0 - ndc.y) * 0.5 * pane_rect.height(),
+    );
+    Some((position, ndc.z))
+}
+
+/// Returns `None` for a depth that maps to infinity, such as the far plane of an
+/// infinite perspective projection
+pub fn unproject_point(
+    matrices: &CameraMatrices,
+    pane_rect: egui::Rect,
+    position: egui::Pos2,
+    depth: f32,
+) -> Option<nalgebra_glm::Vec3> {
+    let ndc_x = (position.x - pane_rect.min.x) / pane_rect.width() * 2.0 - 1.0;
+    let ndc_y = 1.0 - (position.y - pane_rect.min.y) / pane_rect.height() * 2.0;
+    let inverse = nalgebra_glm::inverse(&(matrices.projection * matrices.view));
+    let world = inverse * nalgebra_glm::vec4(ndc_x, ndc_y, depth, 1.0);
+    if world.w.abs() <= f32::EPSILON {
+        return None;
+    }
+    Some(world.xyz() / world.w)
+}
+
+/// Takes the direction between the near plane and a depth inside the view volume,
+/// so it holds for infinite far planes and orthographic projections alike
+pub fn pixel_ray(
+    matrices: &CameraMatrices,
+    pane_rect: egui::Rect,
+    position: egui::Pos2,
+) -> Option<Ray> {
+    let origin = unproject_point(matrices, pane_rect, position, 0.0)?;
+    let through = unproject_point(matrices, pane_rect, position, 0.5)?;
+    let direction = through - origin;
+    if direction == nalgebra_glm::Vec3::zeros() {
+        return None;
+    }
+    Some(Ray {
+        origin,
+        direction: nalgebra_glm::normalize(&direction),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::camera::{Camera, OrthographicCamera, PerspectiveCamera, Projection};
+
+    fn assert_near(actual: nalgebra_glm::Vec3, expected: nalgebra_glm::Vec3) {
+        assert!(
+            nalgebra_glm::distance(&actual, &expected) < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn test_matrices(projection: Projection) -> CameraMatrices {
+        let camera = Camera {
+            projection,
+            fov: 90.0,
+        };
+        let transform = nalgebra_glm::translation(&nalgebra_glm::vec3(0.0, 0.0, 10.0));
+        CameraMatrices {
+            camera_position: nalgebra_glm::vec3(0.0, 0.0, 10.0),
+            projection: camera.projection_matrix(2.0),
+            view: nalgebra_glm::inverse(&transform),
+        }
+    }
+
+    #[test]
+    fn screen_conversions() {
+        let pane_rect =
+            egui::Rect::from_min_size(egui::pos2(100.0, 50.0), egui::vec2(400.0, 200.0));
+        let projections = [
+            Projection::Perspective(PerspectiveCamera {
+                z_far: Some(100.0),
+                z_near: 0.1,
+                ..Default::default()
+            }),
+            Projection::Perspective(PerspectiveCamera::default()),
+            Projection::Orthographic(OrthographicCamera::default()),
+        ];
+        for projection in projections {
+            let matrices = test_matrices(projection);
+
+            // The origin is straight ahead of the camera, in the middle of the pane
+            let (position, depth) =
+                project_point(&matrices, pane_rect, nalgebra_glm::Vec3::zeros()).unwrap();
+            assert!((position - pane_rect.center()).length() < 1e-3);
+            assert!((0.0..=1.0).contains(&depth));
+            let unprojected = unproject_point(&matrices, pane_rect, position, depth).unwrap();
+            assert_near(unprojected, nalgebra_glm::Vec3::zeros());
+
+            let point = nalgebra_glm::vec3(1.5, -0.5, 2.0);
+            let (position, depth) = project_point(&matrices, pane_rect, point).unwrap();
+            assert_near(
+                unproject_point(&matrices, pane_rect, position, depth).unwrap(),
+                point,
+            );
+
+            // The ray through a projected point passes through it
+            let ray = pixel_ray(&matrices, pane_rect, position).unwrap();
+            let distance = nalgebra_glm::dot(&(point - ray.origin), &ray.direction);
+            assert_near(ray.at(distance), point);
+
+            let ray = pixel_ray(&matrices, pane_rect, pane_rect.center()).unwrap();
+            assert_near(ray.direction, -nalgebra_glm::Vec3::z());
+        }
+
+        // Points behind a perspective camera don't project
+        let matrices = test_matrices(Projection::Perspective(PerspectiveCamera::default()));
+        assert!(project_point(&matrices, pane_rect, nalgebra_glm::vec3(0.0, 0.0, 20.0)).is_none());
+
+        // The point at the top left corner of a perspective pane's near plane
+        let (position, depth) = project_point(
+            &matrices,
+            pane_rect,
+            nalgebra_glm::vec3(-0.02, 0.01, 10.0 - 0.01),
+        )
+        .unwrap();
+        assert!((position - pane_rect.min).length() < 1e-2);
+        assert!(depth.abs() < 1e-4);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::context::{
+    actions::{is_action_just_pressed, PAUSE, SLOW_DOWN, SPEED_UP, STEP},
+    get_component, get_component_mut,
+    graphics::RenderMode,
+    query_entities,
+    transform::{query_global_transform, GlobalTransform, LocalTransform, RenderTransform},
+    Context, EntityId, GLOBAL_TRANSFORM, LOCAL_TRANSFORM, RENDER_TRANSFORM,
+};
+
+/// A resource for the fixed-timestep simulation clock
+pub struct Time {
+    /// Fixed updates per second
+    pub tick_rate: f32,
+
+    /// The most fixed updates run in one frame. When a slow frame needs more,
+    /// the simulation time beyond one more step is dropped instead of spiraling,
+    /// so the simulation falls behind real time.
+    pub max_ticks_per_frame: u32,
+
+    /// Scaled time not yet consumed by fixed updates, in seconds
+    pub accumulator: f32,
+
+    /// How far the frame is between the previous and current tick, from 0 to 1
+    pub alpha: f32,
+
+    /// Multiplies the simulation speed in run mode
+    pub time_scale: f32,
+
+    /// Stops fixed updates in run mode
+    pub paused: bool,
+
+    /// Runs a single fixed update on the next frame while paused
+    pub step_requested: bool,
+
+    /// The number of fixed updates run since startup
+    pub tick: u64,
+
+    /// Global transforms before and after the most recent tick, used for interpolation.
+    /// Recorded by the schedule around each tick, and left as they are on frames without one.
+    pub previous_global_transforms: std::collections::HashMap<EntityId, nalgebra_glm::Mat4>,
+    pub current_global_transforms: std::collections::HashMap<EntityId, nalgebra_glm::Mat4>,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            tick_rate: 60.0,
+            max_ticks_per_frame: 5,
+            accumulator: 0.0,
+            alpha: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            step_requested: false,
+            tick: 0,
+            previous_global_transforms: std::collections::HashMap::new(),
+            current_global_transforms: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl Time {
+    /// The simulated seconds advanced by each fixed update
+    pub fn fixed_delta_time(&self) -> f32 {
+        1.0 / self.tick_rate.max(1.0)
+    }
+}
+
+/// Accumulates the frame's scaled delta time and returns how many fixed updates to run
+pub fn accumulate_fixed_ticks(context: &mut Context) -> u32 {
+    let delta_time = context.resources.window.delta_time;
+    let running = context.resources.graphics.render_mode == RenderMode::Run;
+    let time = &mut context.resources.time;
+    let fixed_delta_time = time.fixed_delta_time();
+
+    // Pause and time scale only apply to play sessions, the editor always runs in real time
+    let time_scale = match (running, time.paused) {
+        (false, _) => 1.0,
+        (true, true) => 0.0,
+        (true, false) => time.time_scale.max(0.0),
+    };
+    time.accumulator += delta_time * time_scale;
+
+    let mut ticks =
+        ((time.accumulator / fixed_delta_time).floor() as u32).min(time.max_ticks_per_frame);
+    time.accumulator -= ticks as f32 * fixed_delta_time;
+    // Time the capped ticks could not consume is dropped, keeping at most one step
+    // so the next frame does not have to catch up
+    time.accumulator = time.accumulator.min(fixed_delta_time);
+    if std::mem::take(&mut time.step_requested) && running && time.paused {
+        ticks += 1;
+    }
+    time.alpha = (time.accumulator / fixed_delta_time).clamp(0.0, 1.0);
+    ticks
+}
+
+/// Records the global transforms from before a fixed update
+pub fn begin_fixed_tick(context: &mut Context) {
+    let previous_global_transforms = query_global_transforms(context);
+    context.resources.time.previous_global_transforms = previous_global_transforms;
+}
+
+/// Records the global transforms from after a fixed update
+pub fn end_fixed_tick(context: &mut Context) {
+    let current_global_transforms = query_global_transforms(context);
+    let time = &mut context.resources.time;
+    time.current_global_transforms = current_global_transforms;
+    time.tick += 1;
+}
+
+fn query_global_transforms(
+    context: &Context,
+) -> std::collections::HashMap<EntityId, nalgebra_glm::Mat4> {
+    query_entities(context, LOCAL_TRANSFORM | GLOBAL_TRANSFORM)
+        .into_iter()
+        .map(|entity| (entity, query_global_transform(context, entity)))
+        .collect()
+}
+
+/// Blends the transforms of the previous and current tick by the interpolation alpha
+/// into each entity's render transform, leaving global transforms untouched.
+/// The blend is applied as an offset from the current tick, so entities moved
+/// outside of fixed updates since the tick are drawn where they were moved to,
+/// and entities that did not move in the tick are drawn at their global transform.
+pub fn interpolate_render_transforms_system(context: &mut Context) {
+    let time = &mut context.resources.time;
+    let alpha = time.alpha;
+    let previous_global_transforms = std::mem::take(&mut time.previous_global_transforms);
+    let current_global_transforms = std::mem::take(&mut time.current_global_transforms);
+
+    for entity in query_entities(context, GLOBAL_TRANSFORM | RENDER_TRANSFORM) {
+        let Some(GlobalTransform(global_transform)) =
+            get_component::<GlobalTransform>(context, entity, GLOBAL_TRANSFORM).copied()
+        else {
+            continue;
+        };
+        let tick_offset = match (
+            previous_global_transforms.get(&entity),
+            current_global_transforms.get(&entity),
+        ) {
+            (Some(previous), Some(current)) => current.try_inverse().map(|inverse_current| {
+                interpolate_matrices(previous, current, alpha) * inverse_current
+            }),
+            _ => None,
+        };
+        let Some(RenderTransform(render_transform)) =
+            get_component_mut::<RenderTransform>(context, entity, RENDER_TRANSFORM)
+        else {
+            continue;
+        };
+        *render_transform = match tick_offset {
+            Some(tick_offset) => tick_offset * global_transform,
+            None => global_transform,
+        };
+    }
+
+    let time = &mut context.resources.time;
+    time.previous_global_transforms = previous_global_transforms;
+    time.current_global_transforms = current_global_transforms;
+}
+
+/// Interpolates two affine transforms, slerping their rotations
+pub fn interpolate_matrices(
+    start: &nalgebra_glm::Mat4,
+    end: &nalgebra_glm::Mat4,
+    alpha: f32,
+) -> nalgebra_glm::Mat4 {
+    let start = LocalTransform::from_matrix(start);
+    let end = LocalTransform::from_matrix(end);
+    LocalTransform {
+        translation: nalgebra_glm::lerp(&start.translation, &end.translation, alpha),
+        rotation: nalgebra_glm::quat_slerp(&start.rotation, &end.rotation, alpha),
+        scale: nalgebra_glm::lerp(&start.scale, &end.scale, alpha),
+    }
+    .as_matrix()
+}
+
+/// Run mode shortcuts, by default P pauses, period steps one tick while paused,
+/// and the bracket keys halve or double the time scale.
+/// Scheduled to run only in run mode.
+pub fn time_controls_system(context: &mut Context) {
+    let triggered = |action| is_action_just_pressed(context, action);
+    let (pause, step, slow_down, speed_up) = (
+        triggered(PAUSE),
+        triggered(STEP),
+        triggered(SLOW_DOWN),
+        triggered(SPEED_UP),
+    );
+    let time = &mut context.resources.time;
+    if pause {
+        time.paused = !time.paused;
+    }
+    if step {
+        time.step_requested = true;
+    }
+    if slow_down {
+        time.time_scale = (time.time_scale * 0.5).max(MIN_TIME_SCALE);
+    }
+    if speed_up {
+        time.time_scale = (time.time_scale * 2.0).min(MAX_TIME_SCALE);
+    }
+}
+
+pub const MIN_TIME_SCALE: f32 = 0.125;
+pub const MAX_TIME_SCALE: f32 = 8.0;
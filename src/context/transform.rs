@@ -1,9 +1,9 @@
 use crate::context::{
-    get_component, get_component_mut, query_entities, tree::Parent, Context, EntityId,
-    GLOBAL_TRANSFORM, LOCAL_TRANSFORM, PARENT,
+    add_components, get_component, get_component_mut, query_entities, tree::query_parent, Context,
+    EntityId, GLOBAL_TRANSFORM, LOCAL_TRANSFORM, RENDER_TRANSFORM,
 };
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct LocalTransform {
     pub translation: nalgebra_glm::Vec3,
     pub rotation: nalgebra_glm::Quat,
@@ -27,6 +27,70 @@ impl LocalTransform {
             * nalgebra_glm::scaling(&self.scale)
     }
 
+    /// Decomposes an affine matrix into translation, rotation and scale.
+    /// A reflection becomes a negative x scale, and axes collapsed to zero scale
+    /// take a rotation completed from the remaining axes.
+    pub fn from_matrix(matrix: &nalgebra_glm::Mat4) -> Self {
+        let translation = matrix.column(3).xyz();
+        let columns = [
+            matrix.column(0).xyz(),
+            matrix.column(1).xyz(),
+            matrix.column(2).xyz(),
+        ];
+        let mut scale = nalgebra_glm::vec3(columns[0].norm(), columns[1].norm(), columns[2].norm());
+        if nalgebra_glm::mat4_to_mat3(matrix).determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let axes = [0, 1, 2]
+            .map(|index| (scale[index].abs() > MIN_SCALE).then(|| columns[index] / scale[index]));
+        let [x_axis, y_axis, z_axis] = match axes {
+            [Some(x), Some(y), Some(z)] => [x, y, z],
+            [Some(x), Some(y), None] => [x, y, x.cross(&y).normalize()],
+            [Some(x), None, Some(z)] => [x, z.cross(&x).normalize(), z],
+            [None, Some(y), Some(z)] => [y.cross(&z).normalize(), y, z],
+            [Some(x), None, None] => {
+                let y = perpendicular(&x);
+                [x, y, x.cross(&y)]
+            }
+            [None, Some(y), None] => {
+                let z = perpendicular(&y);
+                [y.cross(&z), y, z]
+            }
+            [None, None, Some(z)] => {
+                let x = perpendicular(&z);
+                [x, z.cross(&x), z]
+            }
+            [None, None, None] => [
+                nalgebra_glm::Vec3::x(),
+                nalgebra_glm::Vec3::y(),
+                nalgebra_glm::Vec3::z(),
+            ],
+        };
+        let rotation_matrix = nalgebra_glm::mat3(
+            x_axis.x, y_axis.x, z_axis.x, x_axis.y, y_axis.y, z_axis.y, x_axis.z, y_axis.z,
+            z_axis.z,
+        );
+        Self {
+            translation,
+            rotation: nalgebra_glm::mat3_to_quat(&rotation_matrix),
+            scale,
+        }
+    }
+
+    /// The rotation as angles in radians about the x, y and z axes, applied in that order
+    pub fn euler_angles(&self) -> nalgebra_glm::Vec3 {
+        let zyx = nalgebra_glm::quat_euler_angles(&self.rotation.normalize());
+        nalgebra_glm::vec3(zyx.z, zyx.y, zyx.x)
+    }
+
+    /// Sets the rotation from angles in radians about the x, y and z axes, applied in that order
+    pub fn set_euler_angles(&mut self, angles: &nalgebra_glm::Vec3) {
+        self.rotation = nalgebra_glm::quat_angle_axis(angles.z, &nalgebra_glm::Vec3::z())
+            * nalgebra_glm::quat_angle_axis(angles.y, &nalgebra_glm::Vec3::y())
+            * nalgebra_glm::quat_angle_axis(angles.x, &nalgebra_glm::Vec3::x());
+    }
+
     pub fn right_vector(&self) -> nalgebra_glm::Vec3 {
         extract_right_vector(&self.as_matrix())
     }
@@ -40,7 +104,7 @@ impl LocalTransform {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct GlobalTransform(pub nalgebra_glm::Mat4);
 
 impl GlobalTransform {
@@ -57,6 +121,35 @@ impl GlobalTransform {
     }
 }
 
+/// The global transform blended between the last two fixed ticks, used only for drawing.
+/// `GlobalTransform` stays the exact, authoritative transform for everything else.
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RenderTransform(pub nalgebra_glm::Mat4);
+
+/// Query for the transform an entity is drawn with,
+/// falling back to its global transform when it has no render transform
+pub fn query_render_transform(context: &Context, entity: EntityId) -> Option<nalgebra_glm::Mat4> {
+    get_component::<RenderTransform>(context, entity, RENDER_TRANSFORM)
+        .map(|RenderTransform(render_transform)| *render_transform)
+        .or_else(|| {
+            get_component::<GlobalTransform>(context, entity, GLOBAL_TRANSFORM)
+                .map(|GlobalTransform(global_transform)| *global_transform)
+        })
+}
+
+/// Scales smaller than this are treated as a collapsed axis when decomposing
+const MIN_SCALE: f32 = 1e-6;
+
+/// A unit vector perpendicular to the given unit vector
+fn perpendicular(axis: &nalgebra_glm::Vec3) -> nalgebra_glm::Vec3 {
+    let reference = if axis.x.abs() < 0.9 {
+        nalgebra_glm::Vec3::x()
+    } else {
+        nalgebra_glm::Vec3::y()
+    };
+    axis.cross(&reference).normalize()
+}
+
 fn extract_right_vector(transform: &nalgebra_glm::Mat4) -> nalgebra_glm::Vec3 {
     nalgebra_glm::vec3(transform[(0, 0)], transform[(1, 0)], transform[(2, 0)])
 }
@@ -69,6 +162,16 @@ fn extract_forward_vector(transform: &nalgebra_glm::Mat4) -> nalgebra_glm::Vec3
     nalgebra_glm::vec3(-transform[(0, 2)], -transform[(1, 2)], -transform[(2, 2)])
 }
 
+/// Hook that gives a child entity the global transform its hierarchy is resolved into
+pub fn on_parent_added(context: &mut Context, entity: EntityId) {
+    add_components(context, entity, GLOBAL_TRANSFORM);
+}
+
+/// Hook that gives an entity with a global transform the render transform it is drawn with
+pub fn on_global_transform_added(context: &mut Context, entity: EntityId) {
+    add_components(context, entity, RENDER_TRANSFORM);
+}
+
 pub fn update_global_transforms_system(context: &mut Context) {
     query_entities(context, LOCAL_TRANSFORM | GLOBAL_TRANSFORM)
         .into_iter()
@@ -85,9 +188,59 @@ pub fn query_global_transform(context: &Context, entity: EntityId) -> nalgebra_g
     else {
         return nalgebra_glm::Mat4::identity();
     };
-    if let Some(Parent(parent)) = get_component::<Parent>(context, entity, PARENT) {
-        query_global_transform(context, *parent) * local_transform.as_matrix()
+    if let Some(parent) = query_parent(context, entity) {
+        query_global_transform(context, parent) * local_transform.as_matrix()
     } else {
         local_transform.as_matrix()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrices_near(actual: &nalgebra_glm::Mat4, expected: &nalgebra_glm::Mat4) {
+        assert!(
+            (actual - expected).abs().max() < 1e-4,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn decompose_matrices() {
+        let transform = LocalTransform {
+            translation: nalgebra_glm::vec3(1.0, 2.0, 3.0),
+            rotation: nalgebra_glm::quat_angle_axis(0.7, &nalgebra_glm::vec3(0.0, 1.0, 0.0)),
+            scale: nalgebra_glm::vec3(2.0, 3.0, 4.0),
+        };
+        let matrix = transform.as_matrix();
+        assert_matrices_near(&LocalTransform::from_matrix(&matrix).as_matrix(), &matrix);
+
+        // Reflections keep their handedness through a negative scale
+        let mirrored = matrix * nalgebra_glm::scaling(&nalgebra_glm::vec3(1.0, -1.0, 1.0));
+        let decomposed = LocalTransform::from_matrix(&mirrored);
+        assert!(decomposed.scale.x < 0.0);
+        assert_matrices_near(&decomposed.as_matrix(), &mirrored);
+
+        // Collapsed axes keep a valid rotation for the remaining ones
+        let flattened = matrix * nalgebra_glm::scaling(&nalgebra_glm::vec3(1.0, 0.0, 1.0));
+        let decomposed = LocalTransform::from_matrix(&flattened);
+        assert!(!decomposed
+            .rotation
+            .coords
+            .iter()
+            .any(|value| value.is_nan()));
+        assert_matrices_near(&decomposed.as_matrix(), &flattened);
+
+        let collapsed = LocalTransform::from_matrix(&nalgebra_glm::scaling(&nalgebra_glm::zero()));
+        assert_eq!(collapsed.rotation, nalgebra_glm::Quat::identity());
+    }
+
+    #[test]
+    fn euler_angles_round_trip() {
+        let mut transform = LocalTransform::default();
+        let angles = nalgebra_glm::vec3(0.3, -0.5, 1.1);
+        transform.set_euler_angles(&angles);
+        assert!((transform.euler_angles() - angles).abs().max() < 1e-4);
+    }
+}
@@ -1,11 +1,41 @@
-use crate::context::{get_component, query_entities, Context, EntityId, PARENT};
+use crate::context::{
+    add_components, component_mask, despawn_entities, get_component, get_component_mut,
+    query_entities, query_entities_filtered, query_entity_components, remove_components,
+    spawn_entity_with_components,
+    transform::{query_global_transform, LocalTransform},
+    Context, EntityComponents, EntityId, QueryFilter, LOCAL_TRANSFORM, PARENT, SIBLING_INDEX,
+};
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Name(pub String);
 
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Parent(pub crate::context::EntityId);
 
+/// The display order of an entity among its siblings
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SiblingIndex(pub usize);
+
+/// What happens to the children of an entity despawned without its descendents.
+/// Children are detached by default, as they were before orphan policies existed,
+/// and keep their place in the world.
+#[derive(Default, Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum OrphanPolicy {
+    /// Despawn the children and their descendents too
+    Despawn,
+    /// Attach the children to the despawned entity's parent, in its place among its siblings
+    ReparentToGrandparent,
+    /// Make the children root entities
+    #[default]
+    MakeRoot,
+}
+
+/// Query for the parent of an entity, ignoring parents that have been despawned
+pub fn query_parent(context: &Context, entity: EntityId) -> Option<EntityId> {
+    let Parent(parent) = get_component::<Parent>(context, entity, PARENT)?;
+    component_mask(context, *parent).map(|_| *parent)
+}
+
 // Query for the child entities of an entity
 pub fn query_children(context: &Context, target_entity: EntityId) -> Vec<EntityId> {
     let mut child_entities = Vec::new();
@@ -20,9 +50,29 @@ pub fn query_children(context: &Context, target_entity: EntityId) -> Vec<EntityI
             }
             child_entities.push(entity);
         });
+    sort_siblings(context, &mut child_entities);
     child_entities
 }
 
+/// Matches the entities at the top of the hierarchy, which are the scenes
+pub const ROOT_ENTITIES: QueryFilter = QueryFilter::with(LOCAL_TRANSFORM).without(PARENT);
+
+/// Query for the entities at the top of the hierarchy, in sibling order
+pub fn query_root_entities(context: &Context) -> Vec<EntityId> {
+    let mut root_entities = query_entities_filtered(context, ROOT_ENTITIES);
+    sort_siblings(context, &mut root_entities);
+    root_entities
+}
+
+/// Sorts entities by their sibling index, placing unordered entities last
+pub fn sort_siblings(context: &Context, entities: &mut [EntityId]) {
+    entities.sort_by_key(|entity| {
+        let index = get_component::<SiblingIndex>(context, *entity, SIBLING_INDEX)
+            .map_or(usize::MAX, |SiblingIndex(index)| *index);
+        (index, entity.id)
+    });
+}
+
 /// Query for all the descendent entities of a target entity
 pub fn query_descendents(context: &Context, target_entity: EntityId) -> Vec<EntityId> {
     let mut descendents = Vec::new();
@@ -48,11 +98,390 @@ pub fn is_descendant_of(
     }
 
     let mut current = entity;
-    while let Some(Parent(parent)) = get_component::<Parent>(context, current, PARENT) {
-        if *parent == ancestor {
+    while let Some(parent) = query_parent(context, current) {
+        if parent == ancestor {
             return true;
         }
-        current = *parent;
+        current = parent;
     }
     false
 }
+
+/// Despawns an entity along with all of its descendents, returning the despawned entities
+pub fn despawn_recursive(context: &mut Context, entity: EntityId) -> Vec<EntityId> {
+    // Children are despawned before their parents so no orphans are left to handle
+    query_descendents(context, entity)
+        .into_iter()
+        .rev()
+        .flat_map(|entity| despawn_entities(context, &[entity]))
+        .collect()
+}
+
+/// Hook that applies the orphan policy to the children of a despawned entity
+pub fn on_entity_despawned(context: &mut Context, entity: EntityId) {
+    let children = query_children(context, entity);
+    if children.is_empty() {
+        return;
+    }
+    match context.resources.orphan_policy {
+        OrphanPolicy::Despawn => {
+            for child in children {
+                despawn_recursive(context, child);
+            }
+        }
+        OrphanPolicy::ReparentToGrandparent => {
+            // The children take the despawned entity's place among its siblings
+            let grandparent = query_parent(context, entity);
+            let siblings = match grandparent {
+                Some(grandparent) => query_children(context, grandparent),
+                None => query_root_entities(context),
+            };
+            let index = siblings
+                .iter()
+                .position(|sibling| *sibling == entity)
+                .unwrap_or(siblings.len());
+            reorder_entities(context, &children, grandparent, index, true);
+        }
+        OrphanPolicy::MakeRoot => {
+            for child in children {
+                reparent_entity(context, child, None, true);
+            }
+        }
+    }
+}
+
+/// A detached copy of an entity and all of its descendents
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct EntityTree {
+    /// The session the tree was copied in, since entity ids only mean anything there
+    pub session: u64,
+    /// The copied entities with their original ids.
+    /// The first entry is always the root of the copied subtree.
+    pub entities: Vec<(EntityId, EntityComponents)>,
+}
+
+/// An id for this process, telling its clipboard contents apart from other instances'
+pub fn session_id() -> u64 {
+    static SESSION_ID: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    *SESSION_ID.get_or_init(|| {
+        use std::hash::{BuildHasher, Hasher};
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.finish()
+    })
+}
+
+/// Copies an entity and all of its descendents out of the world
+pub fn copy_entity_tree(context: &Context, entity: EntityId) -> EntityTree {
+    EntityTree {
+        session: session_id(),
+        entities: query_descendents(context, entity)
+            .into_iter()
+            .filter_map(|entity| Some((entity, query_entity_components(context, entity)?)))
+            .collect(),
+    }
+}
+
+/// Spawns a copy of an entity tree, remapping parent references between
+/// the copied entities and attaching the copied root to a new parent
+pub fn paste_entity_tree(
+    context: &mut Context,
+    entity_tree: &EntityTree,
+    parent: Option<EntityId>,
+) -> Option<EntityId> {
+    let EntityTree { entities, .. } = entity_tree;
+    let mapping = entities
+        .iter()
+        .map(|(original, components)| {
+            (
+                *original,
+                spawn_entity_with_components(context, components.clone()),
+            )
+        })
+        .collect::<std::collections::HashMap<_, _>>();
+
+    for (index, (original, components)) in entities.iter().enumerate() {
+        let new_parent = if index == 0 {
+            parent
+        } else {
+            components
+                .parent
+                .and_then(|Parent(parent)| mapping.get(&parent).copied())
+        };
+        let entity = mapping[original];
+        match new_parent {
+            Some(new_parent) => {
+                add_components(context, entity, PARENT);
+                if let Some(parent) = get_component_mut::<Parent>(context, entity, PARENT) {
+                    *parent = Parent(new_parent);
+                }
+            }
+            None => {
+                remove_components(context, entity, PARENT);
+            }
+        }
+    }
+
+    entities.first().map(|(root, _)| mapping[root])
+}
+
+/// Query for the parent the copied root had, if the tree was copied in this session
+/// and that entity still exists. Ids from other sessions may match unrelated entities.
+pub fn query_original_parent(context: &Context, entity_tree: &EntityTree) -> Option<EntityId> {
+    let EntityTree { session, entities } = entity_tree;
+    if *session != session_id() {
+        return None;
+    }
+    let (_, components) = entities.first()?;
+    let Parent(parent) = components.parent?;
+    component_mask(context, parent).map(|_| parent)
+}
+
+/// Deep copies an entity and its descendents, placing the copy alongside the original
+pub fn duplicate_entity(context: &mut Context, entity: EntityId) -> Option<EntityId> {
+    let parent = get_component::<Parent>(context, entity, PARENT).map(|Parent(parent)| *parent);
+    let entity_tree = copy_entity_tree(context, entity);
+    paste_entity_tree(context, &entity_tree, parent)
+}
+
+/// Changes the parent of an entity, or makes it a root entity if no parent is given.
+/// When keeping the world transform, the local transform is recomputed so
+/// the entity does not move on screen.
+pub fn reparent_entity(
+    context: &mut Context,
+    entity: EntityId,
+    new_parent: Option<EntityId>,
+    keep_world_transform: bool,
+) {
+    let global_transform = query_global_transform(context, entity);
+
+    match new_parent {
+        Some(new_parent) => {
+            add_components(context, entity, PARENT);
+            if let Some(parent) = get_component_mut::<Parent>(context, entity, PARENT) {
+                *parent = Parent(new_parent);
+            }
+        }
+        None => {
+            remove_components(context, entity, PARENT);
+        }
+    }
+
+    if !keep_world_transform {
+        return;
+    }
+    let parent_global_transform = new_parent.map_or_else(nalgebra_glm::Mat4::identity, |parent| {
+        query_global_transform(context, parent)
+    });
+    let new_local_transform = LocalTransform::from_matrix(
+        &(nalgebra_glm::inverse(&parent_global_transform) * global_transform),
+    );
+    if let Some(local_transform) =
+        get_component_mut::<LocalTransform>(context, entity, LOCAL_TRANSFORM)
+    {
+        *local_transform = new_local_transform;
+    }
+}
+
+/// Moves entities as one contiguous block, in the given order, to a position among
+/// the children of a parent, or among the root entities if no parent is given.
+/// The position is an index into the siblings with the moved entities left out.
+pub fn reorder_entities(
+    context: &mut Context,
+    entities: &[EntityId],
+    parent: Option<EntityId>,
+    index: usize,
+    keep_world_transform: bool,
+) {
+    for entity in entities.iter().copied() {
+        let current_parent =
+            get_component::<Parent>(context, entity, PARENT).map(|Parent(parent)| *parent);
+        if current_parent != parent {
+            reparent_entity(context, entity, parent, keep_world_transform);
+        }
+    }
+
+    let mut siblings = match parent {
+        Some(parent) => query_children(context, parent),
+        None => query_root_entities(context),
+    };
+    siblings.retain(|sibling| !entities.contains(sibling));
+    let index = index.min(siblings.len());
+    siblings.splice(index..index, entities.iter().copied());
+
+    for (index, sibling) in siblings.into_iter().enumerate() {
+        add_components(context, sibling, SIBLING_INDEX);
+        if let Some(sibling_index) =
+            get_component_mut::<SiblingIndex>(context, sibling, SIBLING_INDEX)
+        {
+            *sibling_index = SiblingIndex(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{register_on_despawn, spawn_entities, NAME};
+
+    /// Spawns a parent with children in sibling order
+    fn spawn_family(context: &mut Context, count: usize) -> (EntityId, Vec<EntityId>) {
+        let parent = spawn_entities(context, LOCAL_TRANSFORM, 1)[0];
+        let children = spawn_entities(context, LOCAL_TRANSFORM, count);
+        reorder_entities(context, &children, Some(parent), 0, false);
+        (parent, children)
+    }
+
+    fn query_names(context: &Context, entities: &[EntityId]) -> Vec<String> {
+        entities
+            .iter()
+            .filter_map(|entity| get_component::<Name>(context, *entity, NAME))
+            .map(|Name(name)| name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn copy_and_paste_entity_trees() {
+        let mut context = Context::default();
+        let (parent, children) = spawn_family(&mut context, 3);
+        let grandchild = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        reorder_entities(&mut context, &[grandchild], Some(children[1]), 0, false);
+        let outside = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        let named = [parent, children[0], children[1], children[2], grandchild];
+        for (entity, name) in named.into_iter().zip(["p", "a", "b", "c", "g"]) {
+            add_components(&mut context, entity, NAME);
+            *get_component_mut::<Name>(&mut context, entity, NAME).unwrap() = Name(name.into());
+        }
+
+        let mut entity_tree = copy_entity_tree(&context, parent);
+        assert_eq!(entity_tree.entities.len(), 5);
+        assert_eq!(entity_tree.entities[0].0, parent);
+        // A reference to an entity outside the copied tree is dropped
+        let (_, components) = entity_tree
+            .entities
+            .iter_mut()
+            .find(|(entity, _)| *entity == children[2])
+            .unwrap();
+        components.parent = Some(Parent(outside));
+
+        let pasted = paste_entity_tree(&mut context, &entity_tree, None).unwrap();
+        assert!(!named.contains(&pasted));
+        assert_eq!(query_parent(&context, pasted), None);
+        let pasted_children = query_children(&context, pasted);
+        assert_eq!(query_names(&context, &pasted_children), ["a", "b"]);
+        assert!(pasted_children.iter().all(|child| !named.contains(child)));
+        let pasted_grandchildren = query_children(&context, pasted_children[1]);
+        assert_eq!(query_names(&context, &pasted_grandchildren), ["g"]);
+        assert_ne!(pasted_grandchildren[0], grandchild);
+        let detached = query_root_entities(&context)
+            .into_iter()
+            .filter(|entity| !named.contains(entity))
+            .collect::<Vec<_>>();
+        let mut detached_names = query_names(&context, &detached);
+        detached_names.sort();
+        assert_eq!(detached_names, ["c", "p"]);
+
+        // The original is untouched
+        assert_eq!(query_children(&context, parent), children);
+        assert_eq!(query_children(&context, outside), []);
+    }
+
+    #[test]
+    fn original_parents_only_resolve_in_the_same_session() {
+        let mut context = Context::default();
+        let (parent, children) = spawn_family(&mut context, 1);
+        let mut entity_tree = copy_entity_tree(&context, children[0]);
+        assert_eq!(query_original_parent(&context, &entity_tree), Some(parent));
+        entity_tree.session = session_id().wrapping_add(1);
+        assert_eq!(query_original_parent(&context, &entity_tree), None);
+    }
+
+    #[test]
+    fn despawn_recursive_removes_the_subtree() {
+        let mut context = Context::default();
+        register_on_despawn(&mut context, on_entity_despawned);
+        let (parent, children) = spawn_family(&mut context, 2);
+        let (grandparent, _) = spawn_family(&mut context, 0);
+        reorder_entities(&mut context, &[parent], Some(grandparent), 0, false);
+        let grandchild = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        reorder_entities(&mut context, &[grandchild], Some(children[0]), 0, false);
+
+        let mut despawned = despawn_recursive(&mut context, parent);
+        despawned.sort_by_key(|entity| entity.id);
+        assert_eq!(despawned, [parent, children[0], children[1], grandchild]);
+        assert_eq!(query_entities(&context, LOCAL_TRANSFORM), [grandparent]);
+        assert_eq!(query_children(&context, grandparent), []);
+    }
+
+    #[test]
+    fn orphan_policies() {
+        let spawn = |orphan_policy| {
+            let mut context = Context::default();
+            register_on_despawn(&mut context, on_entity_despawned);
+            context.resources.orphan_policy = orphan_policy;
+            let (grandparent, uncles) = spawn_family(&mut context, 3);
+            let parent = uncles[1];
+            let children = spawn_entities(&mut context, LOCAL_TRANSFORM, 2);
+            reorder_entities(&mut context, &children, Some(parent), 0, false);
+            let grandchild = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+            reorder_entities(&mut context, &[grandchild], Some(children[0]), 0, false);
+            despawn_entities(&mut context, &[parent]);
+            (context, grandparent, uncles, children, grandchild)
+        };
+
+        let (context, grandparent, uncles, children, grandchild) = spawn(OrphanPolicy::MakeRoot);
+        assert!(children
+            .iter()
+            .all(|child| get_component::<Parent>(&context, *child, PARENT).is_none()));
+        let roots = query_root_entities(&context);
+        assert!(roots.contains(&grandparent) && children.iter().all(|c| roots.contains(c)));
+        assert_eq!(
+            query_children(&context, grandparent),
+            [uncles[0], uncles[2]]
+        );
+        assert_eq!(query_parent(&context, grandchild), Some(children[0]));
+
+        let (context, grandparent, uncles, children, grandchild) = spawn(OrphanPolicy::Despawn);
+        assert!(children
+            .iter()
+            .chain([&grandchild])
+            .all(|entity| component_mask(&context, *entity).is_none()));
+        assert_eq!(
+            query_children(&context, grandparent),
+            [uncles[0], uncles[2]]
+        );
+
+        let (context, grandparent, uncles, children, grandchild) =
+            spawn(OrphanPolicy::ReparentToGrandparent);
+        assert_eq!(
+            query_children(&context, grandparent),
+            [uncles[0], children[0], children[1], uncles[2]]
+        );
+        assert_eq!(query_parent(&context, grandchild), Some(children[0]));
+    }
+
+    #[test]
+    fn reorder_multiple_entities() {
+        let mut context = Context::default();
+        let (parent, children) = spawn_family(&mut context, 4);
+        let [a, b, t, c] = children[..] else {
+            unreachable!()
+        };
+        assert_eq!(query_children(&context, parent), [a, b, t, c]);
+
+        // After the target, which is at index 0 once the moved entities are left out
+        reorder_entities(&mut context, &[a, b], Some(parent), 1, false);
+        assert_eq!(query_children(&context, parent), [t, a, b, c]);
+
+        // Before the target, keeping the given order
+        reorder_entities(&mut context, &[c, a], Some(parent), 0, false);
+        assert_eq!(query_children(&context, parent), [c, a, t, b]);
+
+        // Into another parent
+        let other = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        reorder_entities(&mut context, &[b, t], Some(other), usize::MAX, false);
+        assert_eq!(query_children(&context, parent), [c, a]);
+        assert_eq!(query_children(&context, other), [b, t]);
+    }
+}
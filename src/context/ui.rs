@@ -1,12 +1,24 @@
 use crate::context::{
-    camera::{Camera, OrthographicCamera, PerspectiveCamera, Projection},
-    get_component, get_component_mut,
+    add_components,
+    camera::{
+        apply_view_preset, Camera, CameraController, CameraControllerMode, OrthographicCamera,
+        PerspectiveCamera, Projection, ViewPreset,
+    },
+    camera_path::{CameraPath, PathInterpolation},
+    component_mask, get_component, get_component_mut,
     graphics::RenderMode,
     paint::{Line, Lines, Quad, Quads},
-    query_entities, spawn_entities,
+    panels::{Panel, INSPECTOR_PANEL, SCENE_TREE_PANEL},
+    query_entities, query_entities_filtered, remove_components, spawn_entities,
+    time::{MAX_TIME_SCALE, MIN_TIME_SCALE},
     transform::LocalTransform,
-    tree::{query_children, query_descendents, Name, Parent},
-    Context, EntityId, CAMERA, GLOBAL_TRANSFORM, LOCAL_TRANSFORM, NAME, PARENT,
+    tree::{
+        copy_entity_tree, despawn_recursive, duplicate_entity, paste_entity_tree, query_children,
+        query_descendents, query_original_parent, query_root_entities, reorder_entities,
+        reparent_entity, EntityTree, Name, OrphanPolicy, Parent, ROOT_ENTITIES,
+    },
+    Context, EntityId, CAMERA, CAMERA_CONTROLLER, CAMERA_PATH, GLOBAL_TRANSFORM, LINES,
+    LOCAL_TRANSFORM, NAME, PARENT, QUADS,
 };
 
 #[derive(Default)]
@@ -15,12 +27,24 @@ pub struct UserInterface {
     pub tile_tree: Option<egui_tiles::Tree<Pane>>,
     pub tile_tree_context: TileTreeContext,
     pub frame_output: Option<(egui::FullOutput, Vec<egui::ClippedPrimitive>)>,
-    pub show_left_panel: bool,
     pub uniform_scaling: bool,
+    pub keep_world_transform: bool,
+    /// Hides the frustums of other cameras in scene panes
+    pub hide_camera_frustums: bool,
     pub consumed_event: bool,
     pub selected_entity: Option<crate::context::EntityId>,
+    /// Entities selected alongside the primary `selected_entity`
+    pub multi_selection: Vec<crate::context::EntityId>,
+    /// Filters the scene tree by name, or by component type when written as "has Camera"
+    pub scene_tree_filter: String,
+    /// Filters the candidates listed when choosing a new parent by name
+    pub parent_picker_filter: String,
+    /// The name entered when saving the current layout as a preset
+    pub layout_name: String,
     pub dragging_viewport: Option<(egui_tiles::TileId, egui::Pos2)>,
     pub broker_address: String,
+    /// Live panel instances for panel panes, keyed by tile and tagged with the panel name
+    pub panels: std::collections::HashMap<egui_tiles::TileId, (String, Box<dyn Panel>)>,
 }
 
 /// A context shared between all the panes in the tile tree
@@ -62,23 +86,43 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
         &mut self, // required by egui_tiles
         pane: &Pane,
     ) -> egui::WidgetText {
-        match pane.kind {
+        match &pane.kind {
             PaneKind::Scene {
                 scene_entity,
                 camera_entity: _,
             } => {
                 if let Some(context) = self.context.and_then(|ctx| unsafe { ctx.as_ref() }) {
-                    if let Some(Name(name)) = get_component::<Name>(context, scene_entity, NAME) {
+                    if let Some(Name(name)) = get_component::<Name>(context, *scene_entity, NAME) {
                         return format!("Scene: {name}").into();
                     }
                 }
                 "Scene".into()
             }
             PaneKind::Color(_) => "Color".into(),
+            PaneKind::Panel(name) => name.as_str().into(),
             PaneKind::Empty => "Empty".into(),
         }
     }
 
+    fn tab_title_for_tile(
+        &mut self, // required by egui_tiles
+        tiles: &egui_tiles::Tiles<Pane>,
+        tile_id: egui_tiles::TileId,
+    ) -> egui::WidgetText {
+        if let Some(context) = self.context.and_then(|ctx| unsafe { ctx.as_ref() }) {
+            if let Some((_, panel)) = context.resources.user_interface.panels.get(&tile_id) {
+                return panel.title(context).into();
+            }
+        }
+        match tiles.get(tile_id) {
+            Some(egui_tiles::Tile::Pane(pane)) => self.tab_title_for_pane(pane),
+            Some(egui_tiles::Tile::Container(container)) => {
+                format!("{:?}", container.kind()).into()
+            }
+            None => "MISSING TILE".into(),
+        }
+    }
+
     fn top_bar_right_ui(
         &mut self, // required by egui_tiles
         _tiles: &egui_tiles::Tiles<Pane>,
@@ -102,7 +146,8 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
         self.tile_rects.insert(tile_id, rect);
 
         if matches!(pane.kind, PaneKind::Scene { .. } | PaneKind::Color(_)) {
-            self.viewport_tiles.insert(tile_id, (pane.kind, rect));
+            self.viewport_tiles
+                .insert(tile_id, (pane.kind.clone(), rect));
         }
 
         if let Some(Some(context)) = self.context.as_mut().map(|ctx| unsafe { ctx.as_mut() }) {
@@ -252,6 +297,14 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                         ui.painter().galley(text_pos, galley, warning_color);
                     }
                 }
+                PaneKind::Panel(_) => {
+                    let mut panel_ui = ui.new_child(
+                        egui::UiBuilder::new()
+                            .max_rect(viewport_rect.shrink(4.0))
+                            .layout(egui::Layout::top_down(egui::Align::Min)),
+                    );
+                    panel_pane_ui(context, &mut panel_ui, tile_id, pane);
+                }
                 _ => {}
             }
 
@@ -268,18 +321,16 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                 ui.add_space(8.0);
 
                 egui::ComboBox::new(format!("type_{}", tile_id.0), "")
-                    .selected_text(match pane.kind {
+                    .selected_text(match &pane.kind {
                         PaneKind::Scene { .. } => "Scene",
                         PaneKind::Color(_) => "Color",
+                        PaneKind::Panel(name) => name.as_str(),
                         PaneKind::Empty => "Empty",
                     })
                     .show_ui(ui, |ui| {
                         // Check if there are any scenes available
-                        let has_scenes = !query_entities(context, LOCAL_TRANSFORM)
-                            .into_iter()
-                            .filter(|e| get_component::<Parent>(context, *e, PARENT).is_none())
-                            .collect::<Vec<_>>()
-                            .is_empty();
+                        let has_scenes =
+                            !query_entities_filtered(context, ROOT_ENTITIES).is_empty();
 
                         let is_scene = matches!(pane.kind, PaneKind::Scene { .. });
                         if has_scenes
@@ -287,9 +338,10 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                             && !is_scene
                         {
                             // When switching to Scene, use existing root node if available
-                            if let Some(existing_scene) = query_entities(context, LOCAL_TRANSFORM)
-                                .into_iter()
-                                .find(|e| get_component::<Parent>(context, *e, PARENT).is_none())
+                            if let Some(existing_scene) =
+                                query_entities_filtered(context, ROOT_ENTITIES)
+                                    .first()
+                                    .copied()
                             {
                                 // Get the camera for this scene
                                 let camera = query_children(context, existing_scene)
@@ -313,6 +365,15 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                             pane.kind = PaneKind::Color(egui::Color32::from_gray(200));
                         }
 
+                        for name in context.resources.panel_registry.names() {
+                            let is_panel =
+                                matches!(&pane.kind, PaneKind::Panel(panel) if *panel == name);
+                            if ui.selectable_label(is_panel, &name).clicked() && !is_panel {
+                                pane.kind = PaneKind::Panel(name);
+                                pane.state = serde_json::Value::Null;
+                            }
+                        }
+
                         let is_empty = matches!(pane.kind, PaneKind::Empty);
                         if ui.selectable_label(is_empty, "Empty").clicked() && !is_empty {
                             pane.kind = PaneKind::Empty;
@@ -389,6 +450,17 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                                 }
                             }
                         });
+
+                    if let Some(camera_entity) = camera_entity {
+                        ui.menu_button("View", |ui| {
+                            for preset in ViewPreset::ALL {
+                                if ui.button(preset.label()).clicked() {
+                                    apply_view_preset(context, camera_entity, preset);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
                 }
             });
 
@@ -406,8 +478,12 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
                 );
             }
 
-            // Handle viewport interaction
-            let viewport_response = ui.allocate_rect(viewport_rect, egui::Sense::click());
+            // Handle viewport interaction, leaving the panel's widgets interactive
+            let viewport_response = if matches!(pane.kind, PaneKind::Panel(_)) {
+                ui.allocate_rect(viewport_rect, egui::Sense::hover())
+            } else {
+                ui.allocate_rect(viewport_rect, egui::Sense::click())
+            };
 
             // Only handle viewport clicks if no color picker is open
             if viewport_response.clicked()
@@ -441,6 +517,9 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
             if self.selected_tile == Some(tile_id) {
                 self.selected_tile = None;
             }
+            if let Some(Some(context)) = self.context.map(|ctx| unsafe { ctx.as_mut() }) {
+                context.resources.user_interface.panels.remove(&tile_id);
+            }
             true // Indicate the tab was successfully closed
         } else {
             false // Indicate the tab wasn't closed
@@ -448,6 +527,67 @@ impl egui_tiles::Behavior<Pane> for TileTreeContext {
     }
 }
 
+/// Draws a panel pane, creating its panel from the registry on first use
+fn panel_pane_ui(
+    context: &mut Context,
+    ui: &mut egui::Ui,
+    tile_id: egui_tiles::TileId,
+    pane: &mut Pane,
+) {
+    let PaneKind::Panel(name) = &pane.kind else {
+        return;
+    };
+
+    // The panel is taken out of the map while it draws so it can borrow the context
+    let panel = match context.resources.user_interface.panels.remove(&tile_id) {
+        Some((panel_name, panel)) if panel_name == *name => Some(panel),
+        _ => context
+            .resources
+            .panel_registry
+            .create(name)
+            .map(|mut panel| {
+                panel.load(&pane.state);
+                panel
+            }),
+    };
+    let Some(mut panel) = panel else {
+        ui.label(
+            egui::RichText::new(format!("Unknown panel \"{name}\""))
+                .color(egui::Color32::from_rgb(220, 130, 0)),
+        );
+        return;
+    };
+
+    ui.push_id(tile_id, |ui| panel.ui(context, ui));
+    context
+        .resources
+        .user_interface
+        .panels
+        .insert(tile_id, (name.clone(), panel));
+}
+
+/// Writes the state of every live panel into its pane, so the layout can be persisted.
+/// Panels are only saved when the layout is, rather than every frame.
+pub fn save_panel_states(context: &mut Context) {
+    let user_interface = &mut context.resources.user_interface;
+    let Some(tile_tree) = user_interface.tile_tree.as_mut() else {
+        return;
+    };
+    for (tile_id, tile) in tile_tree.tiles.iter_mut() {
+        let egui_tiles::Tile::Pane(pane) = tile else {
+            continue;
+        };
+        let PaneKind::Panel(name) = &pane.kind else {
+            continue;
+        };
+        if let Some((panel_name, panel)) = user_interface.panels.get(tile_id) {
+            if panel_name == name {
+                pane.state = panel.save();
+            }
+        }
+    }
+}
+
 pub fn initialize_ui_system(context: &mut Context) {
     context.resources.user_interface.broker_address = "127.0.0.1:9000".to_string();
 
@@ -471,6 +611,235 @@ pub fn initialize_ui_system(context: &mut Context) {
     );
 
     context.resources.user_interface.state = Some(gui_state);
+
+    apply_editor_config(context);
+}
+
+/// Restores the preferences and layout saved in the editor config
+pub fn apply_editor_config(context: &mut Context) {
+    let config = context.resources.editor_config.clone();
+    context.resources.orphan_policy = config.orphan_policy;
+    context.resources.window.lock_cursor_in_run_mode = config.lock_cursor_in_run_mode;
+    let user_interface = &mut context.resources.user_interface;
+    user_interface.uniform_scaling = config.uniform_scaling;
+    user_interface.keep_world_transform = config.keep_world_transform;
+    user_interface.hide_camera_frustums = config.hide_camera_frustums;
+    if let (Some(gui_state), Some(theme)) = (user_interface.state.as_ref(), config.theme) {
+        gui_state.egui_ctx().set_theme(theme);
+    }
+    if let Some(tile_tree) = config.tile_tree {
+        apply_layout(context, tile_tree);
+    }
+}
+
+/// Replaces the tile tree with a layout, rebinding scene panes to live entities
+fn apply_layout(context: &mut Context, mut tile_tree: egui_tiles::Tree<Pane>) {
+    restore_scene_panes(context, &mut tile_tree);
+    let tile_tree_context = &mut context.resources.user_interface.tile_tree_context;
+    tile_tree_context.selected_tile = None;
+    tile_tree_context.viewport_tiles.clear();
+    tile_tree_context.tile_rects.clear();
+    context.resources.user_interface.panels.clear();
+    context.resources.user_interface.tile_tree = Some(tile_tree);
+}
+
+/// Scene panes in a restored layout may refer to entities from a previous session,
+/// whose ids may now belong to unrelated entities. Each missing scene is recreated once,
+/// and each camera that is gone or no longer a camera falls back to the active camera,
+/// or is recreated inside its scene when there is none.
+/// Panes that shared a scene or camera before still share one afterwards.
+fn restore_scene_panes(context: &mut Context, tile_tree: &mut egui_tiles::Tree<Pane>) {
+    let mut scenes = std::collections::HashMap::new();
+    let mut cameras = std::collections::HashMap::new();
+    for (_, tile) in tile_tree.tiles.iter_mut() {
+        let egui_tiles::Tile::Pane(Pane {
+            kind:
+                PaneKind::Scene {
+                    scene_entity,
+                    camera_entity,
+                },
+            ..
+        }) = tile
+        else {
+            continue;
+        };
+
+        let is_scene = component_mask(context, *scene_entity)
+            .is_some_and(|mask| mask & LOCAL_TRANSFORM == LOCAL_TRANSFORM);
+        if !is_scene {
+            if let Some(new_scene) = scenes.get(scene_entity) {
+                *scene_entity = *new_scene;
+            } else {
+                let Pane {
+                    kind:
+                        PaneKind::Scene {
+                            scene_entity: new_scene,
+                            camera_entity: new_camera,
+                        },
+                    ..
+                } = create_scene_pane(context)
+                else {
+                    continue;
+                };
+                scenes.insert(*scene_entity, new_scene);
+                if let (Some(camera), Some(new_camera)) = (*camera_entity, new_camera) {
+                    cameras.insert(camera, new_camera);
+                }
+                *scene_entity = new_scene;
+            }
+        }
+
+        let Some(camera) = *camera_entity else {
+            continue;
+        };
+        if is_camera(context, camera) {
+            continue;
+        }
+        let active_camera = context
+            .resources
+            .active_camera_entity
+            .filter(|active_camera| is_camera(context, *active_camera));
+        let new_camera = match (cameras.get(&camera), active_camera) {
+            (Some(new_camera), _) => *new_camera,
+            (None, Some(active_camera)) => {
+                cameras.insert(camera, active_camera);
+                active_camera
+            }
+            (None, None) => {
+                let camera_count = query_entities(context, CAMERA).len();
+                let new_camera = spawn_scene_camera(
+                    context,
+                    *scene_entity,
+                    format!("Camera {}", camera_count + 1),
+                );
+                cameras.insert(camera, new_camera);
+                new_camera
+            }
+        };
+        *camera_entity = Some(new_camera);
+    }
+}
+
+fn is_camera(context: &Context, entity: EntityId) -> bool {
+    component_mask(context, entity).is_some_and(|mask| mask & CAMERA == CAMERA)
+}
+
+/// The built-in layouts offered alongside user presets
+fn builtin_layout(name: &str) -> Option<egui_tiles::Tree<Pane>> {
+    let mut tiles = egui_tiles::Tiles::default();
+    let mut insert_panes = |count: usize| {
+        (0..count)
+            .map(|_| {
+                let pane = tiles.insert_pane(Pane {
+                    kind: PaneKind::Empty,
+                    ..Default::default()
+                });
+                tiles.insert_tab_tile(vec![pane])
+            })
+            .collect::<Vec<_>>()
+    };
+    let viewports = match name {
+        "Single" => {
+            let panes = insert_panes(1);
+            tiles.insert_tab_tile(panes)
+        }
+        "Columns" => {
+            let panes = insert_panes(2);
+            tiles.insert_horizontal_tile(panes)
+        }
+        "Quad" => {
+            let panes = insert_panes(4);
+            tiles.insert_grid_tile(panes)
+        }
+        _ => return None,
+    };
+
+    // Dock the scene tree above the inspector, to the left of the viewports
+    let mut insert_panel = |name: &str| {
+        let pane = tiles.insert_pane(Pane {
+            kind: PaneKind::Panel(name.to_string()),
+            ..Default::default()
+        });
+        tiles.insert_tab_tile(vec![pane])
+    };
+    let panels = vec![
+        insert_panel(SCENE_TREE_PANEL),
+        insert_panel(INSPECTOR_PANEL),
+    ];
+    let sidebar = tiles.insert_vertical_tile(panels);
+    let root = tiles.insert_horizontal_tile(vec![sidebar, viewports]);
+    if let Some(egui_tiles::Tile::Container(egui_tiles::Container::Linear(linear))) =
+        tiles.get_mut(root)
+    {
+        linear.shares.set_share(sidebar, 1.0);
+        linear.shares.set_share(viewports, 3.0);
+    }
+    Some(egui_tiles::Tree::new("tree", root, tiles))
+}
+
+const BUILTIN_LAYOUTS: [&str; 3] = ["Single", "Columns", "Quad"];
+
+fn layout_menu_ui(context: &mut Context, ui: &mut egui::Ui) {
+    ui.menu_button("Layout", |ui| {
+        for name in BUILTIN_LAYOUTS {
+            if ui.button(name).clicked() {
+                if let Some(layout) = builtin_layout(name) {
+                    apply_layout(context, layout);
+                }
+                ui.close_menu();
+            }
+        }
+
+        let layouts = context.resources.editor_config.layouts.clone();
+        if !layouts.is_empty() {
+            ui.separator();
+        }
+        for (name, layout) in layouts {
+            ui.horizontal(|ui| {
+                if ui.button(&name).clicked() {
+                    apply_layout(context, layout);
+                    ui.close_menu();
+                }
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text("Delete preset")
+                    .clicked()
+                {
+                    context.resources.editor_config.layouts.remove(&name);
+                }
+            });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut context.resources.user_interface.layout_name)
+                    .hint_text("Preset name")
+                    .desired_width(120.0),
+            );
+            let name = context
+                .resources
+                .user_interface
+                .layout_name
+                .trim()
+                .to_string();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save Current"))
+                .clicked()
+            {
+                save_panel_states(context);
+                if let Some(tile_tree) = context.resources.user_interface.tile_tree.clone() {
+                    context
+                        .resources
+                        .editor_config
+                        .layouts
+                        .insert(name, tile_tree);
+                }
+                context.resources.user_interface.layout_name.clear();
+                ui.close_menu();
+            }
+        });
+    });
 }
 
 pub fn scale_factor_changed_system(context: &mut Context, scale_factor: f64) {
@@ -506,8 +875,6 @@ pub fn receive_window_event(
         return;
     }
 
-    let mut new_selected_tile = None;
-
     if let winit::event::WindowEvent::MouseInput {
         state: winit::event::ElementState::Pressed,
         button: winit::event::MouseButton::Left,
@@ -516,52 +883,52 @@ pub fn receive_window_event(
     {
         let mouse_pos = context.resources.input.mouse.position;
         let mouse_pos = egui::pos2(mouse_pos.x, mouse_pos.y);
-
-        context
-            .resources
-            .user_interface
-            .tile_tree_context
-            .selected_tile = None;
-        for (tile_id, rect) in &context
+        let clicked_tile = context
             .resources
             .user_interface
             .tile_tree_context
             .tile_rects
-        {
-            if rect.contains(mouse_pos) {
-                context
-                    .resources
-                    .user_interface
-                    .tile_tree_context
-                    .selected_tile = Some(*tile_id);
-                new_selected_tile = Some(*tile_id);
-                break;
-            }
-        }
+            .iter()
+            .find(|(_, rect)| rect.contains(mouse_pos))
+            .map(|(tile_id, _)| *tile_id);
+        crate::context::replay::record_tile_selection(context, clicked_tile);
+        select_tile(context, clicked_tile);
     }
+}
 
-    if let Some(selected_tile) = new_selected_tile {
-        if let Some((pane_kind, _rect)) = context
-            .resources
-            .user_interface
-            .tile_tree_context
-            .viewport_tiles
-            .get(&selected_tile)
-        {
-            match pane_kind {
-                PaneKind::Scene {
-                    scene_entity: _,
-                    camera_entity,
-                } => {
-                    if let Some(camera) = camera_entity {
-                        // Set both selected and active camera
-                        context.resources.user_interface.selected_entity = Some(*camera);
-                        context.resources.active_camera_entity = Some(*camera);
-                    }
+/// Selects the pane clicked outside the gui, making a scene pane's camera
+/// the selected entity and the active camera
+pub fn select_tile(context: &mut crate::context::Context, tile_id: Option<egui_tiles::TileId>) {
+    context
+        .resources
+        .user_interface
+        .tile_tree_context
+        .selected_tile = tile_id;
+    let Some(tile_id) = tile_id else {
+        return;
+    };
+    if let Some((pane_kind, _rect)) = context
+        .resources
+        .user_interface
+        .tile_tree_context
+        .viewport_tiles
+        .get(&tile_id)
+    {
+        match pane_kind {
+            PaneKind::Scene {
+                scene_entity: _,
+                camera_entity,
+            } => {
+                if let Some(camera) = camera_entity {
+                    // Set both selected and active camera
+                    context.resources.user_interface.selected_entity = Some(*camera);
+                    context.resources.user_interface.multi_selection.clear();
+                    context.resources.active_camera_entity = Some(*camera);
                 }
-                PaneKind::Color(_) => {}
-                PaneKind::Empty => {}
             }
+            PaneKind::Color(_) => {}
+            PaneKind::Panel(_) => {}
+            PaneKind::Empty => {}
         }
     }
 }
@@ -573,17 +940,8 @@ pub fn ensure_tile_tree_system(context: &mut crate::context::Context) {
             return;
         }
     }
-    let mut tiles = egui_tiles::Tiles::default();
-    let mut tab_tiles = vec![];
-
-    let tab_tile_child = tiles.insert_pane(Pane {
-        kind: PaneKind::Empty,
-    });
-    let tab_tile = tiles.insert_tab_tile(vec![tab_tile_child]);
-    tab_tiles.push(tab_tile);
-    let root = tiles.insert_tab_tile(tab_tiles);
-    let tiles = egui_tiles::Tree::new("tree", root, tiles);
-    context.resources.user_interface.tile_tree = Some(tiles);
+    context.resources.user_interface.panels.clear();
+    context.resources.user_interface.tile_tree = builtin_layout("Single");
 }
 
 /// Creates the UI for the frame and
@@ -592,14 +950,20 @@ pub fn create_ui_system(context: &mut crate::context::Context) {
     // Set the context pointer before any UI work
     context.resources.user_interface.tile_tree_context.context = Some(context as *mut _);
 
-    let ui = {
+    let gui_input = {
         let Some(gui_state) = context.resources.user_interface.state.as_mut() else {
             return;
         };
         let Some(window_handle) = context.resources.window.handle.as_ref() else {
             return;
         };
-        let gui_input = gui_state.take_egui_input(window_handle);
+        gui_state.take_egui_input(window_handle)
+    };
+    let gui_input = crate::context::replay::replay_gui_input(context, gui_input);
+    let ui = {
+        let Some(gui_state) = context.resources.user_interface.state.as_ref() else {
+            return;
+        };
         gui_state.egui_ctx().begin_pass(gui_input);
         gui_state.egui_ctx().clone()
     };
@@ -620,7 +984,7 @@ pub fn create_ui_system(context: &mut crate::context::Context) {
 
 fn create_ui(context: &mut crate::context::Context, ui: &egui::Context) {
     top_panel_ui(context, ui);
-    left_panel_ui(context, ui);
+    scene_tree_shortcuts(context, ui);
     central_panel_ui(context, ui);
 }
 
@@ -673,6 +1037,7 @@ fn central_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
                 ) {
                     let new_pane = Pane {
                         kind: PaneKind::Empty,
+                        ..Default::default()
                     };
 
                     let new_child = tile_tree.tiles.insert_pane(new_pane);
@@ -685,6 +1050,25 @@ fn central_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
                     }
                 }
             }
+
+            // Drop panels whose panes were closed or changed type
+            let UserInterface {
+                tile_tree: Some(tile_tree),
+                panels,
+                ..
+            } = &mut context.resources.user_interface
+            else {
+                return;
+            };
+            panels.retain(|tile_id, (name, _)| {
+                matches!(
+                    tile_tree.tiles.get(*tile_id),
+                    Some(egui_tiles::Tile::Pane(Pane {
+                        kind: PaneKind::Panel(panel),
+                        ..
+                    })) if panel == name
+                )
+            });
         });
 }
 
@@ -710,6 +1094,16 @@ fn entity_inspector_ui(
                 {
                     add_components(context, entity, CAMERA);
                 }
+                if get_component::<CameraController>(context, entity, CAMERA_CONTROLLER).is_none()
+                    && ui.button("Camera Controller").clicked()
+                {
+                    add_components(context, entity, CAMERA_CONTROLLER);
+                }
+                if get_component::<CameraPath>(context, entity, CAMERA_PATH).is_none()
+                    && ui.button("Camera Path").clicked()
+                {
+                    add_components(context, entity, CAMERA_PATH);
+                }
                 if get_component::<Lines>(context, entity, LINES).is_none()
                     && ui.button("Lines").clicked()
                 {
@@ -722,6 +1116,7 @@ fn entity_inspector_ui(
                 }
             });
         });
+        keep_changes_ui(context, ui, &[entity]);
     });
 
     ui.separator();
@@ -742,6 +1137,16 @@ fn entity_inspector_ui(
         ui.separator();
     }
 
+    if get_component::<CameraController>(context, entity, CAMERA_CONTROLLER).is_some() {
+        camera_controller_inspector_ui(context, ui, entity);
+        ui.separator();
+    }
+
+    if get_component::<CameraPath>(context, entity, CAMERA_PATH).is_some() {
+        camera_path_inspector_ui(context, ui, entity);
+        ui.separator();
+    }
+
     if get_component::<Lines>(context, entity, LINES).is_some() {
         lines_inspector_ui(context, ui, entity);
         ui.separator();
@@ -753,6 +1158,28 @@ fn entity_inspector_ui(
     }
 }
 
+/// Toggles whether changes made to entities in run mode survive returning to edit mode
+fn keep_changes_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entities: &[EntityId],
+) {
+    let keep_changes = &mut context.resources.play_session.keep_changes;
+    let mut keep = entities.iter().all(|entity| keep_changes.contains(entity));
+    if ui
+        .checkbox(&mut keep, "Keep changes made in run mode")
+        .changed()
+    {
+        for entity in entities {
+            if keep {
+                keep_changes.insert(*entity);
+            } else {
+                keep_changes.remove(entity);
+            }
+        }
+    }
+}
+
 fn name_inspector_ui(
     context: &mut crate::context::Context,
     ui: &mut egui::Ui,
@@ -988,12 +1415,26 @@ fn camera_inspector_ui(
                 }
                 Projection::Orthographic(ortho) => {
                     ui.horizontal(|ui| {
-                        ui.label("Width:");
-                        ui.add(egui::DragValue::new(&mut ortho.x_mag).speed(0.1));
+                        ui.label("Size:");
+                        ui.add(
+                            egui::DragValue::new(&mut ortho.y_mag)
+                                .speed(0.1)
+                                .range(0.01..=f32::MAX),
+                        )
+                        .on_hover_text("Half the visible height, the width follows the aspect");
                     });
                     ui.horizontal(|ui| {
-                        ui.label("Height:");
-                        ui.add(egui::DragValue::new(&mut ortho.y_mag).speed(0.1));
+                        let mut automatic = ortho.aspect_ratio.is_none();
+                        if ui.checkbox(&mut automatic, "Viewport Aspect").changed() {
+                            ortho.aspect_ratio = if automatic { None } else { Some(1.0) };
+                        }
+                        if let Some(aspect_ratio) = &mut ortho.aspect_ratio {
+                            ui.add(
+                                egui::DragValue::new(aspect_ratio)
+                                    .speed(0.01)
+                                    .range(0.01..=100.0),
+                            );
+                        }
                     });
                     ui.horizontal(|ui| {
                         ui.label("Near:");
@@ -1013,113 +1454,312 @@ fn camera_inspector_ui(
     });
 }
 
-fn left_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
-    if !context.resources.user_interface.show_left_panel {
-        return;
-    }
-    egui::SidePanel::left("left").show(ui, |ui| {
-        ui.available_width();
-        egui::ScrollArea::vertical()
-            .id_salt("left_panel")
-            .show(ui, |ui| {
-                // Scene Tree Section
-                ui.collapsing("Scene Tree", |ui| {
-                    // Add Scene button at top level
-                    if ui.button("Add Scene").clicked() {
-                        // Count existing root nodes for scene numbering
-                        let scene_count = query_entities(context, LOCAL_TRANSFORM)
-                            .into_iter()
-                            .filter(|e| get_component::<Parent>(context, *e, PARENT).is_none())
-                            .count();
-
-                        let scene =
-                            spawn_entities(context, NAME | LOCAL_TRANSFORM | GLOBAL_TRANSFORM, 1)
-                                [0];
-
-                        if let Some(name) = get_component_mut::<Name>(context, scene, NAME) {
-                            *name = Name(format!("Scene {}", scene_count + 1));
-                        }
-
-                        // Create camera as child
-                        let camera = spawn_entities(
-                            context,
-                            CAMERA | LOCAL_TRANSFORM | GLOBAL_TRANSFORM | NAME | PARENT,
-                            1,
-                        )[0];
-
-                        if let Some(name) = get_component_mut::<Name>(context, camera, NAME) {
-                            *name = Name(format!("Camera {}", scene_count + 1));
-                        }
+fn camera_path_inspector_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entity: crate::context::EntityId,
+) {
+    use crate::context::*;
 
-                        // Set up camera transform
-                        initialize_camera_transform(context, camera);
+    ui.group(|ui| {
+        ui.label("Camera Path");
+        if let Some(path) = get_component_mut::<CameraPath>(context, entity, CAMERA_PATH) {
+            ui.horizontal(|ui| {
+                ui.label("Interpolation:");
+                ui.radio_value(
+                    &mut path.interpolation,
+                    PathInterpolation::CatmullRom,
+                    "Catmull-Rom",
+                );
+                ui.radio_value(&mut path.interpolation, PathInterpolation::Bezier, "Bezier");
+            });
+            ui.checkbox(&mut path.looping, "Loop");
 
-                        // Parent camera to scene
-                        if let Some(parent) = get_component_mut::<Parent>(context, camera, PARENT) {
-                            *parent = Parent(scene);
+            let bezier = path.interpolation == PathInterpolation::Bezier;
+            let mut keyframes_to_remove = Vec::new();
+            let mut times_changed = false;
+            for (index, keyframe) in path.keyframes.iter_mut().enumerate() {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Keyframe {}", index));
+                        if ui.button("Remove").clicked() {
+                            keyframes_to_remove.push(index);
                         }
-
-                        context.resources.active_camera_entity = Some(camera);
-                        context.resources.user_interface.selected_entity = Some(scene);
-                    }
-
-                    // Only show scene entities at root level
-                    let root_scenes: Vec<_> = query_entities(context, LOCAL_TRANSFORM)
-                        .into_iter()
-                        .filter(|entity| {
-                            get_component::<Parent>(context, *entity, PARENT).is_none()
-                        })
-                        .collect();
-
-                    // Show each scene hierarchy
-                    for scene in root_scenes {
-                        entity_tree_ui(context, ui, scene);
-                    }
-                });
-
-                ui.separator();
-
-                ui.collapsing("Components", |ui| {
-                    if let Some(entity) = context.resources.user_interface.selected_entity {
-                        entity_inspector_ui(context, ui, entity);
-                    } else {
-                        ui.vertical_centered(|ui| {
-                            ui.add_space(8.0);
-                            ui.label(
-                                egui::RichText::new("No entity selected")
-                                    .color(egui::Color32::from_gray(128)),
-                            );
-                            ui.add_space(8.0);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Time:");
+                        times_changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut keyframe.time)
+                                    .speed(0.05)
+                                    .range(0.0..=f32::MAX)
+                                    .suffix(" s"),
+                            )
+                            .changed();
+                        ui.label("FOV:");
+                        ui.add(
+                            egui::DragValue::new(&mut keyframe.fov)
+                                .speed(0.1)
+                                .range(1.0..=120.0)
+                                .suffix("°"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Position:");
+                        ui.label("x");
+                        ui.add(egui::DragValue::new(&mut keyframe.translation.x).speed(0.1));
+                        ui.label("y");
+                        ui.add(egui::DragValue::new(&mut keyframe.translation.y).speed(0.1));
+                        ui.label("z");
+                        ui.add(egui::DragValue::new(&mut keyframe.translation.z).speed(0.1));
+                    });
+                    if bezier {
+                        ui.horizontal(|ui| {
+                            ui.label("Handle:");
+                            ui.label("x");
+                            ui.add(egui::DragValue::new(&mut keyframe.handle.x).speed(0.1));
+                            ui.label("y");
+                            ui.add(egui::DragValue::new(&mut keyframe.handle.y).speed(0.1));
+                            ui.label("z");
+                            ui.add(egui::DragValue::new(&mut keyframe.handle.z).speed(0.1));
                         });
                     }
                 });
-            });
-    });
-}
+            }
 
-fn top_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
-    egui::TopBottomPanel::top("menu").show(ui, |ui| {
-        egui::menu::bar(ui, |ui| {
-            egui::global_theme_preference_switch(ui);
-            ui.separator();
+            // Remove any keyframes marked for deletion (in reverse order to maintain indices)
+            for index in keyframes_to_remove.into_iter().rev() {
+                path.keyframes.remove(index);
+            }
+            if times_changed {
+                path.sort_keyframes();
+            }
 
-            // Add render mode toggle
-            let is_edit_mode = context.resources.graphics.render_mode == RenderMode::Edit;
-            if ui.selectable_label(is_edit_mode, "🔧 Edit").clicked() {
-                context.resources.graphics.render_mode = RenderMode::Edit;
+            if ui
+                .button("Show in Timeline")
+                .on_hover_text("Scrub and play this path from the timeline panel")
+                .clicked()
+            {
+                context.resources.sequencer.path_entity = Some(entity);
             }
-            if ui.selectable_label(!is_edit_mode, "▶ Run").clicked() {
-                context.resources.graphics.render_mode = RenderMode::Run;
+            if ui.button("Remove Component").clicked() {
+                remove_components(context, entity, CAMERA_PATH);
             }
+        }
+    });
+}
 
-            ui.separator();
+fn camera_controller_inspector_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entity: crate::context::EntityId,
+) {
+    use crate::context::*;
 
-            ui.checkbox(
-                &mut context.resources.user_interface.show_left_panel,
-                "Tree",
-            );
-            ui.separator();
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+    ui.group(|ui| {
+        ui.label("Camera Controller");
+        if let Some(controller) =
+            get_component_mut::<CameraController>(context, entity, CAMERA_CONTROLLER)
+        {
+            ui.horizontal(|ui| {
+                ui.label("Mode:");
+                ui.radio_value(&mut controller.mode, CameraControllerMode::Orbit, "Orbit");
+                ui.radio_value(&mut controller.mode, CameraControllerMode::Fly, "Fly");
+                ui.radio_value(&mut controller.mode, CameraControllerMode::Pan2d, "Pan 2D");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Look Sensitivity:");
+                ui.add(
+                    egui::DragValue::new(&mut controller.look_sensitivity)
+                        .speed(0.0001)
+                        .range(0.0..=0.1),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Pan Sensitivity:");
+                ui.add(
+                    egui::DragValue::new(&mut controller.pan_sensitivity)
+                        .speed(0.0001)
+                        .range(0.0..=0.1),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Zoom Sensitivity:");
+                ui.add(
+                    egui::DragValue::new(&mut controller.zoom_sensitivity)
+                        .speed(0.01)
+                        .range(0.0..=0.9),
+                );
+            });
+            if controller.mode == CameraControllerMode::Fly {
+                ui.horizontal(|ui| {
+                    ui.label("Speed:");
+                    ui.add(
+                        egui::DragValue::new(&mut controller.speed)
+                            .speed(0.1)
+                            .range(0.1..=1000.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Acceleration:");
+                    ui.add(
+                        egui::DragValue::new(&mut controller.acceleration)
+                            .speed(0.5)
+                            .range(0.0..=1000.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Boost:");
+                    ui.add(
+                        egui::DragValue::new(&mut controller.boost)
+                            .speed(0.1)
+                            .range(1.0..=20.0),
+                    );
+                });
+            }
+            if ui.button("Remove Component").clicked() {
+                remove_components(context, entity, CAMERA_CONTROLLER);
+            }
+        }
+    });
+}
+
+/// The scene hierarchy with its filter and scene creation controls
+pub fn scene_tree_ui(context: &mut crate::context::Context, ui: &mut egui::Ui) {
+    // Add Scene button at top level
+    if ui.button("Add Scene").clicked() {
+        // Count existing root nodes for scene numbering
+        let scene_count = query_entities_filtered(context, ROOT_ENTITIES).len();
+
+        let scene = spawn_entities(context, NAME | LOCAL_TRANSFORM | GLOBAL_TRANSFORM, 1)[0];
+
+        if let Some(name) = get_component_mut::<Name>(context, scene, NAME) {
+            *name = Name(format!("Scene {}", scene_count + 1));
+        }
+
+        // Create camera as child
+        let camera = spawn_entities(
+            context,
+            CAMERA | CAMERA_CONTROLLER | LOCAL_TRANSFORM | GLOBAL_TRANSFORM | NAME | PARENT,
+            1,
+        )[0];
+
+        if let Some(name) = get_component_mut::<Name>(context, camera, NAME) {
+            *name = Name(format!("Camera {}", scene_count + 1));
+        }
+
+        // Set up camera transform
+        initialize_camera_transform(context, camera);
+
+        // Parent camera to scene
+        if let Some(parent) = get_component_mut::<Parent>(context, camera, PARENT) {
+            *parent = Parent(scene);
+        }
+
+        context.resources.active_camera_entity = Some(camera);
+        select_entity(context, Some(scene));
+    }
+
+    ui.checkbox(
+        &mut context.resources.user_interface.keep_world_transform,
+        "Keep world transform when reparenting",
+    );
+
+    let orphan_policy = &mut context.resources.orphan_policy;
+    egui::ComboBox::from_label("Orphaned children")
+        .selected_text(format!("{orphan_policy:?}"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(orphan_policy, OrphanPolicy::Despawn, "Despawn");
+            ui.selectable_value(
+                orphan_policy,
+                OrphanPolicy::ReparentToGrandparent,
+                "Reparent to grandparent",
+            );
+            ui.selectable_value(orphan_policy, OrphanPolicy::MakeRoot, "Make root");
+        });
+
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(&mut context.resources.user_interface.scene_tree_filter)
+                .hint_text("Name or \"has Camera\""),
+        );
+        if ui.button("✖").clicked() {
+            context.resources.user_interface.scene_tree_filter.clear();
+        }
+    });
+
+    let filter = context.resources.user_interface.scene_tree_filter.clone();
+    let visible_entities = query_scene_tree_filter(context, &filter);
+
+    // Only show scene entities at root level
+    let root_scenes = query_root_entities(context);
+
+    // Show each scene hierarchy
+    for scene in root_scenes {
+        if visible_entities
+            .as_ref()
+            .is_some_and(|visible| !visible.contains(&scene))
+        {
+            continue;
+        }
+        entity_tree_ui(context, ui, scene, visible_entities.as_ref());
+    }
+}
+
+/// The component inspector for the current selection
+pub fn inspector_ui(context: &mut crate::context::Context, ui: &mut egui::Ui) {
+    let selected_entities = query_selected_entities(context);
+    if selected_entities.len() > 1 {
+        multi_entity_inspector_ui(context, ui, &selected_entities);
+    } else if let Some(entity) = context.resources.user_interface.selected_entity {
+        entity_inspector_ui(context, ui, entity);
+    } else {
+        ui.vertical_centered(|ui| {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new("No entity selected").color(egui::Color32::from_gray(128)),
+            );
+            ui.add_space(8.0);
+        });
+    }
+}
+
+fn top_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
+    egui::TopBottomPanel::top("menu").show(ui, |ui| {
+        egui::menu::bar(ui, |ui| {
+            egui::global_theme_preference_switch(ui);
+            ui.separator();
+
+            // Add render mode toggle
+            let is_edit_mode = context.resources.graphics.render_mode == RenderMode::Edit;
+            if ui.selectable_label(is_edit_mode, "🔧 Edit").clicked() {
+                context.resources.graphics.render_mode = RenderMode::Edit;
+            }
+            if ui.selectable_label(!is_edit_mode, "▶ Run").clicked() {
+                context.resources.graphics.render_mode = RenderMode::Run;
+            }
+            ui.checkbox(
+                &mut context.resources.window.lock_cursor_in_run_mode,
+                "🔒 Lock Cursor",
+            )
+            .on_hover_text("Lock and hide the cursor in run mode, Escape releases it");
+            let mut show_camera_frustums = !context.resources.user_interface.hide_camera_frustums;
+            if ui
+                .checkbox(&mut show_camera_frustums, "📷 Frustums")
+                .on_hover_text("Draw the other cameras in scene panes")
+                .changed()
+            {
+                context.resources.user_interface.hide_camera_frustums = !show_camera_frustums;
+            }
+
+            ui.separator();
+            time_controls_ui(context, ui);
+            ui.separator();
+
+            layout_menu_ui(context, ui);
+            ui.separator();
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label(format!(
                     "FPS: {:>3}", // Right-align with width of 3
                     context.resources.window.frames_per_second
@@ -1129,11 +1769,46 @@ fn top_panel_ui(context: &mut crate::context::Context, ui: &egui::Context) {
     });
 }
 
+/// Pause, step and speed controls for play sessions,
+/// also bound to P, period and the bracket keys in run mode
+fn time_controls_ui(context: &mut crate::context::Context, ui: &mut egui::Ui) {
+    let time = &mut context.resources.time;
+    let pause_label = if time.paused { "▶" } else { "⏸" };
+    if ui
+        .button(pause_label)
+        .on_hover_text("Pause or resume the simulation in run mode (P)")
+        .clicked()
+    {
+        time.paused = !time.paused;
+    }
+    if ui
+        .add_enabled(time.paused, egui::Button::new("⏭"))
+        .on_hover_text("Advance one fixed update while paused (.)")
+        .clicked()
+    {
+        time.step_requested = true;
+    }
+    ui.add(
+        egui::DragValue::new(&mut time.time_scale)
+            .range(MIN_TIME_SCALE..=MAX_TIME_SCALE)
+            .speed(0.01)
+            .prefix("x"),
+    )
+    .on_hover_text("Time scale in run mode ([ and ])");
+    ui.add(
+        egui::DragValue::new(&mut time.tick_rate)
+            .range(1.0..=240.0)
+            .suffix(" Hz"),
+    )
+    .on_hover_text("Fixed updates per second");
+}
+
 // Recursively renders the entity tree in the ui system
 fn entity_tree_ui(
     context: &mut crate::context::Context,
     ui: &mut egui::Ui,
     entity: crate::context::EntityId,
+    visible_entities: Option<&std::collections::HashSet<EntityId>>,
 ) {
     use crate::context::*;
 
@@ -1143,12 +1818,20 @@ fn entity_tree_ui(
         format!("Entity {}", entity.id)
     };
 
-    let selected = context.resources.user_interface.selected_entity == Some(entity);
+    let selected = query_selected_entities(context).contains(&entity);
     let is_scene = get_component::<Parent>(context, entity, PARENT).is_none();
     let is_camera = get_component::<Camera>(context, entity, CAMERA).is_some();
 
     let id = ui.make_persistent_id(entity.id);
-    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+    let mut collapsing_state =
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true);
+
+    // Expand the ancestors of filter matches
+    if visible_entities.is_some() {
+        collapsing_state.set_open(true);
+    }
+
+    collapsing_state
         .show_header(ui, |ui| {
             ui.horizontal(|ui| {
                 let prefix = if is_scene {
@@ -1159,9 +1842,22 @@ fn entity_tree_ui(
                     "🔵"
                 };
 
-                let response = ui.selectable_label(selected, format!("{prefix} {name}"));
+                let response = ui
+                    .selectable_label(selected, format!("{prefix} {name}"))
+                    .interact(egui::Sense::drag());
+                if response.drag_started() {
+                    response.dnd_set_drag_payload(entity);
+                }
+                entity_tree_drop_ui(context, ui, &response, entity);
                 if response.clicked() {
-                    context.resources.user_interface.selected_entity = Some(entity);
+                    let modifiers = ui.input(|input| input.modifiers);
+                    if modifiers.command {
+                        toggle_entity_selection(context, entity);
+                    } else if modifiers.shift {
+                        select_entity_range(context, entity, visible_entities);
+                    } else {
+                        select_entity(context, Some(entity));
+                    }
                     if is_camera {
                         context.resources.active_camera_entity = Some(entity);
                     }
@@ -1188,68 +1884,80 @@ fn entity_tree_ui(
                             *parent = Parent(entity);
                         }
 
-                        context.resources.user_interface.selected_entity = Some(new_entity);
+                        select_entity(context, Some(new_entity));
                         ui.close_menu();
                     }
 
-                    // Add "Reparent to..." submenu
-                    ui.menu_button("Reparent to...", |ui| {
-                        // Get all potential parent entities (excluding this entity and its descendants)
-                        let all_entities = query_entities(context, LOCAL_TRANSFORM);
-                        let descendants = query_descendents(context, entity);
-
-                        for potential_parent in all_entities {
-                            // Skip if this would create a cycle
-                            if descendants.contains(&potential_parent) || potential_parent == entity
-                            {
-                                continue;
-                            }
+                    if ui
+                        .add(egui::Button::new("Duplicate").shortcut_text("Ctrl+D"))
+                        .clicked()
+                    {
+                        if let Some(duplicate) = duplicate_entity(context, entity) {
+                            select_entity(context, Some(duplicate));
+                        }
+                        ui.close_menu();
+                    }
 
-                            // Get name of potential parent
-                            let parent_name = if let Some(Name(name)) =
-                                get_component::<Name>(context, potential_parent, NAME)
-                            {
-                                name.to_string()
-                            } else {
-                                format!("Entity {}", potential_parent.id)
-                            };
+                    if ui
+                        .add(egui::Button::new("Copy").shortcut_text("Ctrl+C"))
+                        .clicked()
+                    {
+                        copy_entity_to_clipboard(context, entity);
+                        ui.close_menu();
+                    }
 
-                            if ui.button(parent_name).clicked() {
-                                // Check for cycles one more time before reparenting
-                                if !would_create_cycle(context, entity, potential_parent) {
-                                    // Add PARENT component if it doesn't exist
-                                    if get_component::<Parent>(context, entity, PARENT).is_none() {
-                                        add_components(context, entity, PARENT);
-                                    }
+                    if ui.add(egui::Button::new("Paste as Child")).clicked() {
+                        let text = context
+                            .resources
+                            .user_interface
+                            .state
+                            .as_mut()
+                            .and_then(|state| state.clipboard_text());
+                        if let Some(text) = text {
+                            paste_entity_from_text(context, &text, Some(entity));
+                        }
+                        ui.close_menu();
+                    }
 
-                                    // Update parent
-                                    if let Some(parent) =
-                                        get_component_mut::<Parent>(context, entity, PARENT)
-                                    {
-                                        *parent = Parent(potential_parent);
-                                    }
-                                }
-                                ui.close_menu();
-                            }
+                    ui.menu_button("Reparent to...", |ui| {
+                        if let Some(new_parent) = parent_picker_ui(context, ui, &[entity]) {
+                            let keep_world_transform =
+                                context.resources.user_interface.keep_world_transform;
+                            reparent_entity(
+                                context,
+                                entity,
+                                Some(new_parent),
+                                keep_world_transform,
+                            );
+                            ui.close_menu();
                         }
 
                         // Option to remove parent (make root)
                         if get_component::<Parent>(context, entity, PARENT).is_some() {
                             ui.separator();
                             if ui.button("Make Root (Remove Parent)").clicked() {
-                                remove_components(context, entity, PARENT);
+                                let keep_world_transform =
+                                    context.resources.user_interface.keep_world_transform;
+                                reparent_entity(context, entity, None, keep_world_transform);
                                 ui.close_menu();
                             }
                         }
                     });
 
-                    if ui.button("Remove").clicked() {
-                        despawn_entities(context, &[entity]);
-                        let descendents = query_descendents(context, entity);
-                        for entity in descendents {
-                            despawn_entities(context, &[entity]);
+                    if selected && !context.resources.user_interface.multi_selection.is_empty() {
+                        ui.separator();
+                        if ui.button("Delete Selected").clicked() {
+                            let selected_entities = query_selected_entities(context);
+                            despawn_entity_trees(context, &selected_entities);
+                            select_entity(context, None);
+                            ui.close_menu();
                         }
-                        context.resources.user_interface.selected_entity = None;
+                        ui.separator();
+                    }
+
+                    if ui.button("Remove").clicked() {
+                        despawn_recursive(context, entity);
+                        select_entity(context, None);
                         ui.close_menu();
                     }
                 });
@@ -1258,10 +1966,569 @@ fn entity_tree_ui(
         .body(|ui| {
             query_children(context, entity)
                 .into_iter()
+                .filter(|child| visible_entities.is_none_or(|visible| visible.contains(child)))
                 .for_each(|child| {
-                    entity_tree_ui(context, ui, child);
+                    entity_tree_ui(context, ui, child, visible_entities);
+                });
+        });
+}
+
+/// Component types that can be named in scene tree filters and bulk operations
+const COMPONENT_NAMES: &[(&str, u32)] = &[
+    ("Camera", CAMERA),
+    ("Camera Controller", CAMERA_CONTROLLER),
+    ("Camera Path", CAMERA_PATH),
+    ("Lines", LINES),
+    ("Name", NAME),
+    ("Parent", PARENT),
+    ("Quads", QUADS),
+    ("Transform", LOCAL_TRANSFORM),
+];
+
+/// Selects a single entity, clearing any multi-selection
+pub fn select_entity(context: &mut crate::context::Context, entity: Option<EntityId>) {
+    context.resources.user_interface.selected_entity = entity;
+    context.resources.user_interface.multi_selection.clear();
+}
+
+/// Query for every selected entity, with the primary selection first
+pub fn query_selected_entities(context: &crate::context::Context) -> Vec<EntityId> {
+    let user_interface = &context.resources.user_interface;
+    user_interface
+        .selected_entity
+        .into_iter()
+        .chain(user_interface.multi_selection.iter().copied())
+        .filter(|entity| component_mask(context, *entity).is_some())
+        .collect()
+}
+
+/// Adds or removes an entity from the selection
+fn toggle_entity_selection(context: &mut crate::context::Context, entity: EntityId) {
+    let user_interface = &mut context.resources.user_interface;
+    if user_interface.selected_entity == Some(entity) {
+        user_interface.selected_entity = if user_interface.multi_selection.is_empty() {
+            None
+        } else {
+            Some(user_interface.multi_selection.remove(0))
+        };
+    } else if let Some(index) = user_interface
+        .multi_selection
+        .iter()
+        .position(|selected| *selected == entity)
+    {
+        user_interface.multi_selection.remove(index);
+    } else if user_interface.selected_entity.is_none() {
+        user_interface.selected_entity = Some(entity);
+    } else {
+        user_interface.multi_selection.push(entity);
+    }
+}
+
+/// Selects every visible entity between the primary selection and an entity
+fn select_entity_range(
+    context: &mut crate::context::Context,
+    entity: EntityId,
+    visible_entities: Option<&std::collections::HashSet<EntityId>>,
+) {
+    let Some(anchor) = context.resources.user_interface.selected_entity else {
+        select_entity(context, Some(entity));
+        return;
+    };
+    let order = query_scene_tree_order(context, visible_entities);
+    let (Some(anchor_index), Some(entity_index)) = (
+        order.iter().position(|e| *e == anchor),
+        order.iter().position(|e| *e == entity),
+    ) else {
+        select_entity(context, Some(entity));
+        return;
+    };
+    let range = anchor_index.min(entity_index)..=anchor_index.max(entity_index);
+    context.resources.user_interface.multi_selection = order[range]
+        .iter()
+        .copied()
+        .filter(|e| *e != anchor)
+        .collect();
+}
+
+/// Query for the visible entities of the scene tree in display order
+fn query_scene_tree_order(
+    context: &crate::context::Context,
+    visible_entities: Option<&std::collections::HashSet<EntityId>>,
+) -> Vec<EntityId> {
+    let mut order = Vec::new();
+    let mut stack = query_root_entities(context);
+    stack.reverse();
+    while let Some(entity) = stack.pop() {
+        if visible_entities.is_some_and(|visible| !visible.contains(&entity)) {
+            continue;
+        }
+        order.push(entity);
+        stack.extend(query_children(context, entity).into_iter().rev());
+    }
+    order
+}
+
+/// Query for the entities matching a scene tree filter along with all of their ancestors.
+/// Returns `None` when the filter is empty and everything should be shown.
+fn query_scene_tree_filter(
+    context: &crate::context::Context,
+    filter: &str,
+) -> Option<std::collections::HashSet<EntityId>> {
+    let filter = filter.trim().to_lowercase();
+    if filter.is_empty() {
+        return None;
+    }
+
+    let matches = if let Some(component_name) = filter.strip_prefix("has ") {
+        COMPONENT_NAMES
+            .iter()
+            .find(|(name, _)| name.to_lowercase() == component_name.trim())
+            .map(|(_, mask)| query_entities(context, *mask))
+            .unwrap_or_default()
+    } else {
+        query_entities(context, NAME)
+            .into_iter()
+            .filter(|entity| {
+                get_component::<Name>(context, *entity, NAME)
+                    .is_some_and(|Name(name)| name.to_lowercase().contains(&filter))
+            })
+            .collect()
+    };
+
+    let mut visible_entities = std::collections::HashSet::new();
+    for entity in matches {
+        let mut current = Some(entity);
+        while let Some(entity) = current {
+            if !visible_entities.insert(entity) {
+                break;
+            }
+            current =
+                get_component::<Parent>(context, entity, PARENT).map(|Parent(parent)| *parent);
+        }
+    }
+    Some(visible_entities)
+}
+
+/// Despawns entities along with all of their descendents
+fn despawn_entity_trees(context: &mut crate::context::Context, entities: &[EntityId]) {
+    for entity in entities {
+        despawn_recursive(context, *entity);
+    }
+}
+
+/// Bulk operations and shared field editing for several selected entities
+fn multi_entity_inspector_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entities: &[EntityId],
+) {
+    ui.label(format!("{} entities selected", entities.len()));
+
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label("Add Component:");
+            egui::ComboBox::new("multi_add_component", "").show_ui(ui, |ui| {
+                for (name, mask) in COMPONENT_NAMES {
+                    if matches!(*mask, NAME | PARENT) {
+                        continue;
+                    }
+                    if ui.button(*name).clicked() {
+                        for entity in entities {
+                            add_components(context, *entity, *mask);
+                        }
+                    }
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Remove Component:");
+            egui::ComboBox::new("multi_remove_component", "").show_ui(ui, |ui| {
+                for (name, mask) in COMPONENT_NAMES {
+                    if *mask == PARENT {
+                        continue;
+                    }
+                    if ui.button(*name).clicked() {
+                        for entity in entities {
+                            remove_components(context, *entity, *mask);
+                        }
+                    }
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Reparent to:");
+            egui::ComboBox::new("multi_reparent", "")
+                // Typing into the search field should not close the list
+                .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                .show_ui(ui, |ui| {
+                    let keep_world_transform =
+                        context.resources.user_interface.keep_world_transform;
+                    if ui.button("Make Root (Remove Parent)").clicked() {
+                        for entity in entities {
+                            reparent_entity(context, *entity, None, keep_world_transform);
+                        }
+                        ui.memory_mut(|memory| memory.close_popup());
+                    }
+                    ui.separator();
+                    if let Some(new_parent) = parent_picker_ui(context, ui, entities) {
+                        for entity in entities {
+                            reparent_entity(
+                                context,
+                                *entity,
+                                Some(new_parent),
+                                keep_world_transform,
+                            );
+                        }
+                        ui.memory_mut(|memory| memory.close_popup());
+                    }
                 });
         });
+        if ui.button("Delete Selected").clicked() {
+            despawn_entity_trees(context, entities);
+            select_entity(context, None);
+        }
+        keep_changes_ui(context, ui, entities);
+    });
+
+    ui.separator();
+
+    let shares_components = |context: &crate::context::Context, mask: u32| {
+        entities
+            .iter()
+            .all(|entity| component_mask(context, *entity).is_some_and(|m| m & mask == mask))
+    };
+
+    if shares_components(context, LOCAL_TRANSFORM) {
+        multi_local_transform_inspector_ui(context, ui, entities);
+        ui.separator();
+    }
+
+    if shares_components(context, CAMERA) {
+        multi_camera_inspector_ui(context, ui, entities);
+        ui.separator();
+    }
+}
+
+/// Lists the entities that can become the parent of all the given entities,
+/// searchable by name. The entities and their descendents are never listed.
+/// Returns the candidate that was clicked.
+fn parent_picker_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entities: &[EntityId],
+) -> Option<EntityId> {
+    let mut filter = std::mem::take(&mut context.resources.user_interface.parent_picker_filter);
+    ui.add(egui::TextEdit::singleline(&mut filter).hint_text("Search..."));
+    let search = filter.trim().to_lowercase();
+    context.resources.user_interface.parent_picker_filter = filter;
+
+    let excluded = entities
+        .iter()
+        .flat_map(|entity| query_descendents(context, *entity))
+        .collect::<std::collections::HashSet<_>>();
+
+    let mut picked = None;
+    egui::ScrollArea::vertical()
+        .max_height(240.0)
+        .show(ui, |ui| {
+            for candidate in query_scene_tree_order(context, None) {
+                if excluded.contains(&candidate) {
+                    continue;
+                }
+                let name = match get_component::<Name>(context, candidate, NAME) {
+                    Some(Name(name)) => name.to_string(),
+                    None => format!("Entity {}", candidate.id),
+                };
+                if !search.is_empty() && !name.to_lowercase().contains(&search) {
+                    continue;
+                }
+                if ui.button(name).clicked() {
+                    picked = Some(candidate);
+                }
+            }
+        });
+    picked
+}
+
+/// Edits the transform fields shared by several entities.
+/// Only the fields changed this frame are written to every entity.
+fn multi_local_transform_inspector_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entities: &[EntityId],
+) {
+    let Some(original) =
+        get_component::<LocalTransform>(context, entities[0], LOCAL_TRANSFORM).copied()
+    else {
+        return;
+    };
+    let mut edited = original;
+    let original_rotation = nalgebra_glm::degrees(&original.euler_angles());
+    let mut rotation_degrees = original_rotation;
+
+    ui.group(|ui| {
+        ui.label("Transform");
+        ui.group(|ui| {
+            ui.label("Position");
+            ui.horizontal(|ui| {
+                ui.label("X");
+                ui.add(egui::DragValue::new(&mut edited.translation.x).speed(0.1));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(&mut edited.translation.y).speed(0.1));
+                ui.label("Z");
+                ui.add(egui::DragValue::new(&mut edited.translation.z).speed(0.1));
+            });
+        });
+        ui.group(|ui| {
+            ui.label("Rotation");
+            ui.horizontal(|ui| {
+                ui.label("X");
+                ui.add(egui::DragValue::new(&mut rotation_degrees.x).suffix("°"));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(&mut rotation_degrees.y).suffix("°"));
+                ui.label("Z");
+                ui.add(egui::DragValue::new(&mut rotation_degrees.z).suffix("°"));
+            });
+        });
+        ui.group(|ui| {
+            ui.label("Scale");
+            ui.horizontal(|ui| {
+                ui.label("X");
+                ui.add(egui::DragValue::new(&mut edited.scale.x).speed(0.1));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(&mut edited.scale.y).speed(0.1));
+                ui.label("Z");
+                ui.add(egui::DragValue::new(&mut edited.scale.z).speed(0.1));
+            });
+        });
+    });
+
+    if edited == original && rotation_degrees == original_rotation {
+        return;
+    }
+    for entity in entities {
+        let Some(local_transform) =
+            get_component_mut::<LocalTransform>(context, *entity, LOCAL_TRANSFORM)
+        else {
+            continue;
+        };
+        if rotation_degrees != original_rotation {
+            // Each entity keeps its own angles on the axes that were not edited
+            let mut angles = local_transform.euler_angles();
+            for axis in 0..3 {
+                if rotation_degrees[axis] != original_rotation[axis] {
+                    angles[axis] = rotation_degrees[axis].to_radians();
+                }
+            }
+            local_transform.set_euler_angles(&angles);
+        }
+        for axis in 0..3 {
+            if edited.translation[axis] != original.translation[axis] {
+                local_transform.translation[axis] = edited.translation[axis];
+            }
+            if edited.scale[axis] != original.scale[axis] {
+                local_transform.scale[axis] = edited.scale[axis];
+            }
+        }
+    }
+}
+
+/// Edits the camera fields shared by several entities
+fn multi_camera_inspector_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    entities: &[EntityId],
+) {
+    let Some(mut fov) = get_component::<Camera>(context, entities[0], CAMERA).map(|c| c.fov) else {
+        return;
+    };
+
+    let changed = ui
+        .group(|ui| {
+            ui.label("Camera");
+            ui.horizontal(|ui| {
+                ui.label("FOV:");
+                ui.add(egui::Slider::new(&mut fov, 1.0..=120.0).suffix("°"))
+                    .changed()
+            })
+            .inner
+        })
+        .inner;
+
+    if !changed {
+        return;
+    }
+    for entity in entities {
+        if let Some(camera) = get_component_mut::<Camera>(context, *entity, CAMERA) {
+            camera.fov = fov;
+        }
+    }
+}
+
+/// Where a dragged entity will land relative to the entity it is dropped on
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum TreeDropPosition {
+    Before,
+    Inside,
+    After,
+}
+
+/// Highlights and handles entities dragged onto an entity tree node
+fn entity_tree_drop_ui(
+    context: &mut crate::context::Context,
+    ui: &mut egui::Ui,
+    response: &egui::Response,
+    target: EntityId,
+) {
+    let Some(pointer) = ui.ctx().pointer_interact_pos() else {
+        return;
+    };
+    let rect = response.rect;
+    let position = if pointer.y < rect.top() + rect.height() * 0.25 {
+        TreeDropPosition::Before
+    } else if pointer.y > rect.bottom() - rect.height() * 0.25 {
+        TreeDropPosition::After
+    } else {
+        TreeDropPosition::Inside
+    };
+
+    if let Some(dragged) = response.dnd_hover_payload::<EntityId>() {
+        if *dragged != target {
+            let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(251, 146, 60));
+            match position {
+                TreeDropPosition::Before => {
+                    ui.painter().hline(rect.x_range(), rect.top(), stroke);
+                }
+                TreeDropPosition::Inside => {
+                    ui.painter().rect_stroke(rect, 2.0, stroke);
+                }
+                TreeDropPosition::After => {
+                    ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+                }
+            }
+        }
+    }
+
+    let Some(dragged) = response.dnd_release_payload::<EntityId>() else {
+        return;
+    };
+    let dragged = *dragged;
+    if dragged == target {
+        return;
+    }
+
+    // Dragging any selected entity moves the whole selection
+    let selected_entities = query_selected_entities(context);
+    let dragged_entities = if selected_entities.contains(&dragged) {
+        selected_entities
+    } else {
+        vec![dragged]
+    };
+    if dragged_entities.contains(&target) {
+        return;
+    }
+
+    let (new_parent, index) = match position {
+        TreeDropPosition::Inside => (Some(target), usize::MAX),
+        TreeDropPosition::Before | TreeDropPosition::After => {
+            let parent =
+                get_component::<Parent>(context, target, PARENT).map(|Parent(parent)| *parent);
+            let mut siblings = match parent {
+                Some(parent) => query_children(context, parent),
+                None => query_root_entities(context),
+            };
+            siblings.retain(|sibling| !dragged_entities.contains(sibling));
+            let target_index = siblings
+                .iter()
+                .position(|sibling| *sibling == target)
+                .unwrap_or(siblings.len());
+            let index = if position == TreeDropPosition::After {
+                target_index + 1
+            } else {
+                target_index
+            };
+            (parent, index)
+        }
+    };
+
+    let keep_world_transform = context.resources.user_interface.keep_world_transform;
+    let dragged_entities = dragged_entities
+        .into_iter()
+        .filter(|dragged| {
+            !new_parent.is_some_and(|new_parent| would_create_cycle(context, *dragged, new_parent))
+        })
+        .collect::<Vec<_>>();
+    reorder_entities(
+        context,
+        &dragged_entities,
+        new_parent,
+        index,
+        keep_world_transform,
+    );
+}
+
+/// Duplicate, copy and paste the selected entity with keyboard shortcuts
+fn scene_tree_shortcuts(context: &mut crate::context::Context, ui: &egui::Context) {
+    if ui.wants_keyboard_input() {
+        return;
+    }
+    let selected_entity = context.resources.user_interface.selected_entity;
+
+    let duplicate_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+    if ui.input_mut(|input| input.consume_shortcut(&duplicate_shortcut)) {
+        if let Some(duplicate) =
+            selected_entity.and_then(|entity| duplicate_entity(context, entity))
+        {
+            select_entity(context, Some(duplicate));
+        }
+    }
+
+    let events = ui.input(|input| input.events.clone());
+    for event in events {
+        match event {
+            egui::Event::Copy => {
+                if let Some(entity) = selected_entity {
+                    copy_entity_to_clipboard(context, entity);
+                }
+            }
+            egui::Event::Paste(text) => {
+                paste_entity_from_text(context, &text, None);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Serializes an entity and its descendents to the system clipboard
+fn copy_entity_to_clipboard(context: &mut crate::context::Context, entity: EntityId) {
+    let entity_tree = copy_entity_tree(context, entity);
+    let text = match serde_json::to_string(&entity_tree) {
+        Ok(text) => text,
+        Err(error) => {
+            log::error!("Failed to serialize entity for the clipboard: {error}");
+            return;
+        }
+    };
+    if let Some(state) = context.resources.user_interface.state.as_mut() {
+        state.set_clipboard_text(text);
+    }
+}
+
+/// Deserializes an entity tree from clipboard text and spawns it under a parent,
+/// or where it was copied from when no parent is given
+fn paste_entity_from_text(
+    context: &mut crate::context::Context,
+    text: &str,
+    parent: Option<EntityId>,
+) {
+    let Ok(entity_tree) = serde_json::from_str::<EntityTree>(text) else {
+        log::debug!("Clipboard does not contain an entity");
+        return;
+    };
+    let parent = parent.or_else(|| query_original_parent(context, &entity_tree));
+    if let Some(pasted) = paste_entity_tree(context, &entity_tree, parent) {
+        select_entity(context, Some(pasted));
+    }
 }
 
 fn local_transform_inspector_ui(
@@ -1377,10 +2644,7 @@ fn update_tile_mappings(
 
 fn create_scene_pane(context: &mut crate::context::Context) -> Pane {
     // Count only root nodes (no Parent component) for scene numbering
-    let scene_count = query_entities(context, LOCAL_TRANSFORM)
-        .into_iter()
-        .filter(|e| get_component::<Parent>(context, *e, PARENT).is_none())
-        .count();
+    let scene_count = query_entities_filtered(context, ROOT_ENTITIES).len();
 
     // Create root entity (scene)
     let scene = spawn_entities(context, NAME | LOCAL_TRANSFORM | GLOBAL_TRANSFORM, 1)[0];
@@ -1390,16 +2654,34 @@ fn create_scene_pane(context: &mut crate::context::Context) -> Pane {
         *name = Name(format!("Scene {}", scene_count + 1));
     }
 
-    // Create camera as child
+    let camera = spawn_scene_camera(context, scene, format!("Camera {}", scene_count + 1));
+
+    // Set as active camera
+    context.resources.active_camera_entity = Some(camera);
+
+    Pane {
+        kind: PaneKind::Scene {
+            scene_entity: scene,
+            camera_entity: Some(camera),
+        },
+        ..Default::default()
+    }
+}
+
+/// Creates a named camera as a child of a scene
+fn spawn_scene_camera(
+    context: &mut crate::context::Context,
+    scene: EntityId,
+    camera_name: String,
+) -> EntityId {
     let camera = spawn_entities(
         context,
-        CAMERA | LOCAL_TRANSFORM | GLOBAL_TRANSFORM | NAME | PARENT,
+        CAMERA | CAMERA_CONTROLLER | LOCAL_TRANSFORM | GLOBAL_TRANSFORM | NAME | PARENT,
         1,
     )[0];
 
-    // Set camera name to match scene number
     if let Some(name) = get_component_mut::<Name>(context, camera, NAME) {
-        *name = Name(format!("Camera {}", scene_count + 1));
+        *name = Name(camera_name);
     }
 
     // Set up camera transform
@@ -1410,15 +2692,7 @@ fn create_scene_pane(context: &mut crate::context::Context) -> Pane {
         *parent = Parent(scene);
     }
 
-    // Set as active camera
-    context.resources.active_camera_entity = Some(camera);
-
-    Pane {
-        kind: PaneKind::Scene {
-            scene_entity: scene,
-            camera_entity: Some(camera),
-        },
-    }
+    camera
 }
 
 fn initialize_camera_transform(context: &mut crate::context::Context, camera: EntityId) {
@@ -1463,13 +2737,15 @@ fn would_create_cycle(
     false
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum PaneKind {
     Scene {
         scene_entity: EntityId,
         camera_entity: Option<EntityId>,
     },
     Color(egui::Color32),
+    /// A panel from the `PanelRegistry`, by name
+    Panel(String),
     Empty,
 }
 
@@ -1479,7 +2755,10 @@ impl Default for PaneKind {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Pane {
     pub kind: PaneKind,
+    /// The persisted state of a panel pane
+    #[serde(default)]
+    pub state: serde_json::Value,
 }
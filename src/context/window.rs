@@ -1,4 +1,4 @@
-use crate::context::Context;
+use crate::context::{graphics::RenderMode, input::MouseState, Context};
 
 #[derive(Default)]
 pub struct Window {
@@ -34,6 +34,26 @@ pub struct Window {
 
     /// Milliseconds that the process has been running continuously
     pub uptime_milliseconds: u64,
+
+    /// Whether the window has keyboard focus
+    pub focused: bool,
+
+    /// Lock and hide the cursor in run mode, with raw mouse motion driving the camera
+    pub lock_cursor_in_run_mode: bool,
+
+    /// Whether the cursor is currently locked and hidden
+    pub cursor_grabbed: bool,
+
+    /// The user released the grab with the exit action or by leaving the window,
+    /// it is grabbed again by clicking in the window
+    pub cursor_released: bool,
+}
+
+/// Sent when the window's physical size changes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WindowResized {
+    pub width: u32,
+    pub height: u32,
 }
 
 pub fn scale_factor_changed_system(
@@ -116,18 +136,94 @@ pub fn initialize_window_system(
     context: &mut Context,
     event_loop: &winit::event_loop::ActiveEventLoop,
 ) {
-    let Some(window_handle) = create_window(event_loop) else {
+    let Some(window_handle) = create_window(event_loop, context.resources.editor_config.window)
+    else {
         return;
     };
     context.resources.window.handle = Some(window_handle.clone());
     context.resources.window.last_frame_start_instant = Some(std::time::Instant::now());
     context.resources.window.scale_factor = 1.0;
+    context.resources.window.focused = true;
+}
+
+/// Tracks window focus, leaving the window releases a cursor grab
+pub fn receive_window_event(context: &mut Context, event: &winit::event::WindowEvent) {
+    let winit::event::WindowEvent::Focused(focused) = event else {
+        return;
+    };
+    let window = &mut context.resources.window;
+    window.focused = *focused;
+    if !*focused {
+        window.cursor_released = true;
+    }
+}
+
+/// Grabs the cursor in run mode when enabled, and frees it when leaving run mode,
+/// losing focus or after the user releases it. Clicking in the window grabs it again.
+/// Scheduled to run only while no input recording is replaying.
+pub fn cursor_grab_system(context: &mut Context) {
+    let running = context.resources.graphics.render_mode == RenderMode::Run;
+    let clicked = context
+        .resources
+        .input
+        .mouse
+        .just_pressed
+        .contains(MouseState::LEFT_CLICKED);
+    // Clicks on the gui, like the top bar in run mode, keep the cursor free
+    let over_gui = context
+        .resources
+        .user_interface
+        .state
+        .as_ref()
+        .is_some_and(|gui_state| gui_state.egui_ctx().wants_pointer_input());
+    let window = &mut context.resources.window;
+    if !running || (clicked && !over_gui && window.focused) {
+        window.cursor_released = false;
+    }
+    let grabbed =
+        running && window.lock_cursor_in_run_mode && window.focused && !window.cursor_released;
+    if grabbed != window.cursor_grabbed {
+        set_cursor_grabbed(context, grabbed);
+    }
+}
+
+/// Locks and hides the cursor, or frees and shows it
+fn set_cursor_grabbed(context: &mut Context, grabbed: bool) {
+    let window = &mut context.resources.window;
+    window.cursor_grabbed = grabbed;
+    let Some(window_handle) = window.handle.as_ref() else {
+        return;
+    };
+    let result = if grabbed {
+        // Not every platform supports both grab modes
+        window_handle
+            .set_cursor_grab(winit::window::CursorGrabMode::Locked)
+            .or_else(|_| window_handle.set_cursor_grab(winit::window::CursorGrabMode::Confined))
+    } else {
+        window_handle.set_cursor_grab(winit::window::CursorGrabMode::None)
+    };
+    if let Err(error) = result {
+        log::error!("Failed to set cursor grab: {error}");
+    }
+    window_handle.set_cursor_visible(!grabbed);
 }
 
 fn create_window(
     event_loop: &winit::event_loop::ActiveEventLoop,
+    window_config: Option<crate::context::config::WindowConfig>,
 ) -> Option<std::sync::Arc<winit::window::Window>> {
     let mut attributes = winit::window::Window::default_attributes().with_title("Abyssal");
+    if let Some(crate::context::config::WindowConfig {
+        width,
+        height,
+        x,
+        y,
+    }) = window_config
+    {
+        attributes = attributes
+            .with_inner_size(winit::dpi::PhysicalSize::new(width, height))
+            .with_position(winit::dpi::PhysicalPosition::new(x, y));
+    }
     if let Some(icon) = load_icon(include_bytes!("../icon/icon.png")) {
         attributes.window_icon = Some(icon);
     }
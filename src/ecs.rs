@@ -1,12 +1,20 @@
+/// Declares a context with its component tables, resources and events.
+/// Attributes before the context name are applied to the entity and storage types,
+/// `EntityId`, `EntityComponents` and `WorldSnapshot` among them, for example serde derives
+/// so entities can be copied to text and whole worlds saved.
 #[macro_export]
 macro_rules! ecs {
     (
+        $(#[$entity_attr:meta])*
         $context:ident {
             $($name:ident: $type:ty => $mask:ident),* $(,)?
         }
         $resources:ident {
             $($(#[$attr:meta])*  $resource_name:ident: $resource_type:ty),* $(,)?
         }
+        $events:ident {
+            $($(#[$event_attr:meta])* $event_name:ident: $event_type:ty),* $(,)?
+        }
     ) => {
 
         /// Component masks
@@ -35,6 +43,7 @@ macro_rules! ecs {
 
         /// Entity ID, an index into storage and a generation counter to prevent stale references
         #[derive(Default, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+        $(#[$entity_attr])*
         pub struct EntityId {
             pub id: u32,
             pub generation: u32,
@@ -48,13 +57,15 @@ macro_rules! ecs {
         }
 
         // Handles allocation and reuse of entity IDs
-        #[derive(Default)]
+        #[derive(Default, Clone)]
+        $(#[$entity_attr])*
         pub struct EntityAllocator {
             next_id: u32,
             free_ids: Vec<(u32, u32)>, // (id, next_generation)
         }
 
         #[derive(Default, Copy, Clone)]
+        $(#[$entity_attr])*
         struct EntityLocation {
             generation: u32,
             table_index: u16,
@@ -63,7 +74,8 @@ macro_rules! ecs {
         }
 
         /// Entity location cache for quick access
-        #[derive(Default)]
+        #[derive(Default, Clone)]
+        $(#[$entity_attr])*
         pub struct EntityLocations {
             locations: Vec<EntityLocation>,
         }
@@ -75,29 +87,226 @@ macro_rules! ecs {
             pub tables: Vec<ComponentArrays>,
             pub allocator: EntityAllocator,
             pub resources: $resources,
+            pub events: $events,
+            pub hooks: ComponentHooks,
             table_edges: Vec<TableEdges>,
         }
 
+        /// Called with the entity whose components changed
+        pub type ComponentHook = fn(&mut $context, EntityId);
+
+        /// Lifecycle hooks, each registered for a component mask and invoked
+        /// when any component in that mask changes
+        #[derive(Default, Clone)]
+        pub struct ComponentHooks {
+            on_add: Vec<(u32, ComponentHook)>,
+            on_remove: Vec<(u32, ComponentHook)>,
+            on_replace: Vec<(u32, ComponentHook)>,
+            on_despawn: Vec<ComponentHook>,
+        }
+
+        #[allow(dead_code)]
+        /// Register a hook that runs after any component in the mask is added to an entity
+        pub fn register_on_add(context: &mut $context, mask: u32, hook: ComponentHook) {
+            context.hooks.on_add.push((mask, hook));
+        }
+
+        #[allow(dead_code)]
+        /// Register a hook that runs before any component in the mask is removed from an entity,
+        /// including when the entity is despawned
+        pub fn register_on_remove(context: &mut $context, mask: u32, hook: ComponentHook) {
+            context.hooks.on_remove.push((mask, hook));
+        }
+
+        #[allow(dead_code)]
+        /// Register a hook that runs before any component in the mask is overwritten
+        /// by `set_entity_components`
+        pub fn register_on_replace(context: &mut $context, mask: u32, hook: ComponentHook) {
+            context.hooks.on_replace.push((mask, hook));
+        }
+
+        #[allow(dead_code)]
+        /// Register a hook that runs before any entity is despawned, before its on-remove hooks
+        pub fn register_on_despawn(context: &mut $context, hook: ComponentHook) {
+            context.hooks.on_despawn.push(hook);
+        }
+
+        fn run_hooks(context: &mut $context, hooks: &[(u32, ComponentHook)], entity: EntityId, mask: u32) {
+            let hooks = hooks
+                .iter()
+                .filter(|(hook_mask, _)| hook_mask & mask != 0)
+                .map(|(_, hook)| *hook)
+                .collect::<Vec<_>>();
+            for hook in hooks {
+                hook(context, entity);
+            }
+        }
+
         /// Resources
         #[derive(Default)]
         pub struct $resources {
             $($(#[$attr])* pub $resource_name: $resource_type,)*
         }
 
-        /// Component Table
+        /// Event queues, including the built-in entity and component events
         #[derive(Default)]
+        pub struct $events {
+            pub entity_spawned: EventQueue<EntitySpawned>,
+            pub entity_despawned: EventQueue<EntityDespawned>,
+            pub components_added: EventQueue<ComponentsAdded>,
+            pub components_removed: EventQueue<ComponentsRemoved>,
+            $($(#[$event_attr])* pub $event_name: EventQueue<$event_type>,)*
+        }
+
+        /// Sent when an entity is spawned
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct EntitySpawned {
+            pub entity: EntityId,
+        }
+
+        /// Sent when an entity is despawned
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct EntityDespawned {
+            pub entity: EntityId,
+        }
+
+        /// Sent when components are added to an existing entity
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct ComponentsAdded {
+            pub entity: EntityId,
+            pub mask: u32,
+        }
+
+        /// Sent when components are removed from an entity that stays alive
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct ComponentsRemoved {
+            pub entity: EntityId,
+            pub mask: u32,
+        }
+
+        /// A double-buffered queue of events.
+        /// Events can be read during the frame they are sent and the frame after,
+        /// then they are dropped by `update_events`.
+        pub struct EventQueue<T> {
+            previous: Vec<T>,
+            current: Vec<T>,
+            /// The id of the first event in `previous`
+            previous_start: u64,
+        }
+
+        impl<T> Default for EventQueue<T> {
+            fn default() -> Self {
+                Self {
+                    previous: Vec::new(),
+                    current: Vec::new(),
+                    previous_start: 0,
+                }
+            }
+        }
+
+        /// A reader's position in an event queue, so each reader sees every event once
+        #[derive(Default, Debug, Copy, Clone, PartialEq)]
+        pub struct EventCursor {
+            next: u64,
+        }
+
+        #[allow(dead_code)]
+        impl<T> EventQueue<T> {
+            /// Queue an event for every reader
+            pub fn send(&mut self, event: T) {
+                self.current.push(event);
+            }
+
+            /// Read the events this cursor has not seen yet, oldest first
+            pub fn read<'a>(&'a self, cursor: &mut EventCursor) -> impl Iterator<Item = &'a T> + 'a {
+                let skipped = cursor.next.saturating_sub(self.previous_start) as usize;
+                cursor.next = self.previous_start + (self.previous.len() + self.current.len()) as u64;
+                self.previous.iter().chain(self.current.iter()).skip(skipped)
+            }
+
+            /// Whether no events are buffered
+            pub fn is_empty(&self) -> bool {
+                self.previous.is_empty() && self.current.is_empty()
+            }
+
+            /// Drop the previous frame's events and start buffering a new frame
+            pub fn update(&mut self) {
+                self.previous_start += self.previous.len() as u64;
+                std::mem::swap(&mut self.previous, &mut self.current);
+                self.current.clear();
+            }
+        }
+
+        #[allow(dead_code)]
+        /// Swap the buffers of every event queue, called once per frame
+        pub fn update_events(context: &mut $context) {
+            context.events.entity_spawned.update();
+            context.events.entity_despawned.update();
+            context.events.components_added.update();
+            context.events.components_removed.update();
+            $(context.events.$event_name.update();)*
+        }
+
+        /// Component Table
+        #[derive(Default, Clone)]
+        $(#[$entity_attr])*
         pub struct ComponentArrays {
             $(pub $name: Vec<$type>,)*
             pub entity_indices: Vec<EntityId>,
             pub mask: u32,
         }
 
+        /// A detached copy of every component attached to a single entity
+        #[derive(Default, Clone)]
+        $(#[$entity_attr])*
+        pub struct EntityComponents {
+            $(pub $name: Option<$type>,)*
+        }
+
         #[derive(Copy, Clone, Default)]
-        struct TableEdges {
+        $(#[$entity_attr])*
+        pub struct TableEdges {
             add_edges: [Option<usize>; COMPONENT_COUNT],
             remove_edges: [Option<usize>; COMPONENT_COUNT],
         }
 
+        /// A copy of all entity and component storage, leaving resources untouched.
+        /// Restoring it brings back every entity with its original id.
+        #[derive(Default, Clone)]
+        $(#[$entity_attr])*
+        pub struct WorldSnapshot {
+            entity_locations: EntityLocations,
+            tables: Vec<ComponentArrays>,
+            allocator: EntityAllocator,
+            table_edges: Vec<TableEdges>,
+        }
+
+        #[allow(dead_code)]
+        /// Copy every entity and component out of storage
+        pub fn snapshot_world(context: &$context) -> WorldSnapshot {
+            WorldSnapshot {
+                entity_locations: context.entity_locations.clone(),
+                tables: context.tables.clone(),
+                allocator: context.allocator.clone(),
+                table_edges: context.table_edges.clone(),
+            }
+        }
+
+        #[allow(dead_code)]
+        /// Replace every entity and component with a snapshot
+        pub fn restore_world(context: &mut $context, snapshot: WorldSnapshot) {
+            let WorldSnapshot {
+                entity_locations,
+                tables,
+                allocator,
+                table_edges,
+            } = snapshot;
+            context.entity_locations = entity_locations;
+            context.tables = tables;
+            context.allocator = allocator;
+            context.table_edges = table_edges;
+        }
+
         fn get_component_index(mask: u32) -> Option<usize> {
             match mask {
                 $($mask => Some(Component::$mask as _),)*
@@ -136,6 +345,7 @@ macro_rules! ecs {
                     ),
                 );
                 entities.push(entity);
+                context.events.entity_spawned.send(EntitySpawned { entity });
                 insert_location(
                     &mut context.entity_locations,
                     entity,
@@ -143,9 +353,76 @@ macro_rules! ecs {
                 );
             }
 
+            let hooks = context.hooks.on_add.clone();
+            for entity in &entities {
+                run_hooks(context, &hooks, *entity, mask);
+            }
+
             entities
         }
 
+        #[allow(dead_code)]
+        /// Spawn a new entity from a detached set of components
+        pub fn spawn_entity_with_components(context: &mut $context, components: EntityComponents) -> EntityId {
+            let mut mask = 0;
+            $(
+                if components.$name.is_some() {
+                    mask |= $mask;
+                }
+            )*
+            let table_index = get_or_create_table(context, mask);
+            let entity = create_entity(context);
+            let EntityComponents { $($name,)* } = components;
+            add_to_table(&mut context.tables[table_index], entity, ($($name,)*));
+            insert_location(
+                &mut context.entity_locations,
+                entity,
+                (table_index, context.tables[table_index].entity_indices.len() - 1),
+            );
+            context.events.entity_spawned.send(EntitySpawned { entity });
+            let hooks = context.hooks.on_add.clone();
+            run_hooks(context, &hooks, entity, mask);
+            entity
+        }
+
+        #[allow(dead_code)]
+        /// Replace every component attached to an entity, adding and removing
+        /// components so the entity has exactly the components that are set
+        pub fn set_entity_components(context: &mut $context, entity: EntityId, components: EntityComponents) -> bool {
+            let Some(current_mask) = component_mask(context, entity) else {
+                return false;
+            };
+            let mut mask = 0;
+            $(
+                if components.$name.is_some() {
+                    mask |= $mask;
+                }
+            )*
+            if current_mask & !mask != 0 {
+                remove_components(context, entity, current_mask & !mask);
+            }
+            if mask & !current_mask != 0 {
+                add_components(context, entity, mask & !current_mask);
+            }
+            let hooks = context.hooks.on_replace.clone();
+            run_hooks(context, &hooks, entity, current_mask & mask);
+            let EntityComponents { $($name,)* } = components;
+            $(
+                if let (Some(value), Some(component)) = ($name, get_component_mut::<$type>(context, entity, $mask)) {
+                    *component = value;
+                }
+            )*
+            true
+        }
+
+        #[allow(dead_code)]
+        /// Copy every component attached to an entity out of storage
+        pub fn query_entity_components(context: &$context, entity: EntityId) -> Option<EntityComponents> {
+            let (table_index, array_index) = get_location(&context.entity_locations, entity)?;
+            let ($($name,)*) = get_components(&context.tables[table_index], array_index);
+            Some(EntityComponents { $($name,)* })
+        }
+
         #[allow(dead_code)]
         /// Query for all entities that match the component mask
         pub fn query_entities(context: &$context, mask: u32) -> Vec<EntityId> {
@@ -172,6 +449,100 @@ macro_rules! ecs {
             result
         }
 
+        /// Matches tables by components they must have, must not have, and need at least one of
+        #[derive(Default, Debug, Copy, Clone, PartialEq)]
+        pub struct QueryFilter {
+            pub with: u32,
+            pub without: u32,
+            pub any_of: u32,
+        }
+
+        #[allow(dead_code)]
+        impl QueryFilter {
+            /// Match entities that have every component in the mask
+            pub const fn with(mask: u32) -> Self {
+                Self {
+                    with: mask,
+                    without: 0,
+                    any_of: 0,
+                }
+            }
+
+            /// Exclude entities that have any component in the mask
+            pub const fn without(self, mask: u32) -> Self {
+                Self {
+                    without: self.without | mask,
+                    ..self
+                }
+            }
+
+            /// Only match entities that have at least one component in the mask
+            pub const fn any_of(self, mask: u32) -> Self {
+                Self {
+                    any_of: self.any_of | mask,
+                    ..self
+                }
+            }
+
+            /// Whether a table with this component mask matches the filter
+            pub const fn matches(&self, mask: u32) -> bool {
+                mask & self.with == self.with
+                    && mask & self.without == 0
+                    && (self.any_of == 0 || mask & self.any_of != 0)
+            }
+        }
+
+        /// Borrowed components of a single entity, `None` for components it doesn't have
+        #[allow(dead_code)]
+        pub struct EntityRef<'a> {
+            pub entity: EntityId,
+            $(pub $name: Option<&'a $type>,)*
+        }
+
+        #[allow(dead_code)]
+        /// Query for all entities that match the filter
+        pub fn query_entities_filtered(context: &$context, filter: QueryFilter) -> Vec<EntityId> {
+            let mut result = Vec::new();
+            for table in &context.tables {
+                if filter.matches(table.mask) {
+                    result.extend(
+                        table
+                            .entity_indices
+                            .iter()
+                            .copied()
+                            .filter(|&e| context.entity_locations.locations[e.id as usize].allocated),
+                    );
+                }
+            }
+            result
+        }
+
+        #[allow(dead_code)]
+        /// Query for the components of all entities that match the filter,
+        /// with components outside the table's mask left as `None`
+        pub fn query_components(context: &$context, filter: QueryFilter) -> Vec<EntityRef<'_>> {
+            let mut result = Vec::new();
+            for table in &context.tables {
+                if !filter.matches(table.mask) {
+                    continue;
+                }
+                for (array_index, entity) in table.entity_indices.iter().enumerate() {
+                    if !context.entity_locations.locations[entity.id as usize].allocated {
+                        continue;
+                    }
+                    result.push(EntityRef {
+                        entity: *entity,
+                        $($name: if table.mask & $mask != 0 {
+                            Some(&table.$name[array_index])
+                        } else {
+                            None
+                        },)*
+                    });
+                }
+            }
+            result
+        }
+
         #[allow(dead_code)]
         /// Query for the first entity that matches the component mask
         /// Returns as soon as a match is found, instead of running for all entities
@@ -255,6 +626,21 @@ macro_rules! ecs {
         #[allow(dead_code)]
         /// Despawn a batch of entities
         pub fn despawn_entities(context: &mut $context, entities: &[EntityId]) -> Vec<EntityId> {
+            // Hooks run while the components are still readable
+            let despawn_hooks = context.hooks.on_despawn.clone();
+            let hooks = context.hooks.on_remove.clone();
+            for &entity in entities {
+                if component_mask(context, entity).is_none() {
+                    continue;
+                }
+                for hook in &despawn_hooks {
+                    hook(context, entity);
+                }
+                if let Some(mask) = component_mask(context, entity) {
+                    run_hooks(context, &hooks, entity, mask);
+                }
+            }
+
             let mut despawned = Vec::with_capacity(entities.len());
             let mut tables_to_update = Vec::new();
 
@@ -308,6 +694,10 @@ macro_rules! ecs {
                 table.entity_indices.swap_remove(array_idx);
             }
 
+            for entity in &despawned {
+                context.events.entity_despawned.send(EntityDespawned { entity: *entity });
+            }
+
             despawned
         }
 
@@ -330,6 +720,12 @@ macro_rules! ecs {
                     target_table.unwrap_or_else(|| get_or_create_table(context, current_mask | mask));
 
                 move_entity(context, entity, table_index, array_index, new_table_index);
+                context.events.components_added.send(ComponentsAdded {
+                    entity,
+                    mask: mask & !current_mask,
+                });
+                let hooks = context.hooks.on_add.clone();
+                run_hooks(context, &hooks, entity, mask & !current_mask);
                 true
             } else {
                 false
@@ -339,6 +735,11 @@ macro_rules! ecs {
         #[allow(dead_code)]
         /// Remove components from an entity
         pub fn remove_components(context: &mut $context, entity: EntityId, mask: u32) -> bool {
+            if let Some(current_mask) = component_mask(context, entity) {
+                // Hooks run while the components are still readable, and may move the entity
+                let hooks = context.hooks.on_remove.clone();
+                run_hooks(context, &hooks, entity, current_mask & mask);
+            }
             if let Some((table_index, array_index)) = get_location(&context.entity_locations, entity) {
                 let current_mask = context.tables[table_index].mask;
                 if current_mask & mask == 0 {
@@ -356,6 +757,10 @@ macro_rules! ecs {
                     target_table.unwrap_or_else(|| get_or_create_table(context, current_mask & !mask));
 
                 move_entity(context, entity, table_index, array_index, new_table_index);
+                context.events.components_removed.send(ComponentsRemoved {
+                    entity,
+                    mask: current_mask & mask,
+                });
                 true
             } else {
                 false
@@ -545,3 +950,190 @@ macro_rules! has_components {
         $table.mask & $mask == $mask
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{window::WindowResized, *};
+
+    fn resized(width: u32) -> WindowResized {
+        WindowResized { width, height: 1 }
+    }
+
+    fn read_widths(context: &Context, cursor: &mut EventCursor) -> Vec<u32> {
+        context
+            .events
+            .window_resized
+            .read(cursor)
+            .map(|event| event.width)
+            .collect()
+    }
+
+    #[test]
+    fn events_survive_one_update() {
+        let mut context = Context::default();
+        let mut cursor = EventCursor::default();
+        context.events.window_resized.send(resized(1));
+        assert_eq!(read_widths(&context, &mut cursor), [1]);
+        // A cursor never reads the same event twice
+        assert!(read_widths(&context, &mut cursor).is_empty());
+
+        context.events.window_resized.send(resized(2));
+        update_events(&mut context);
+        assert_eq!(read_widths(&context, &mut cursor), [2]);
+
+        // A cursor created after the update still sees the previous frame's events
+        let mut late_cursor = EventCursor::default();
+        assert_eq!(read_widths(&context, &mut late_cursor), [1, 2]);
+
+        update_events(&mut context);
+        assert!(context.events.window_resized.is_empty());
+        assert!(read_widths(&context, &mut EventCursor::default()).is_empty());
+
+        // Cursors pick up new events after their old ones are dropped
+        context.events.window_resized.send(resized(3));
+        assert_eq!(read_widths(&context, &mut cursor), [3]);
+        assert_eq!(read_widths(&context, &mut late_cursor), [3]);
+    }
+
+    thread_local! {
+        static HOOK_CALLS: std::cell::RefCell<Vec<(&'static str, bool)>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    /// Records a hook call and whether the entity had a camera when it ran
+    fn record_hook(context: &Context, name: &'static str, entity: EntityId) {
+        let has_camera = get_component::<camera::Camera>(context, entity, CAMERA).is_some();
+        HOOK_CALLS.with(|calls| calls.borrow_mut().push((name, has_camera)));
+    }
+
+    fn take_hook_calls() -> Vec<(&'static str, bool)> {
+        HOOK_CALLS.with(|calls| std::mem::take(&mut *calls.borrow_mut()))
+    }
+
+    #[test]
+    fn hooks_fire_in_order() {
+        let mut context = Context::default();
+        register_on_add(&mut context, CAMERA, |context, entity| {
+            record_hook(context, "add", entity)
+        });
+        register_on_remove(&mut context, CAMERA, |context, entity| {
+            record_hook(context, "remove", entity)
+        });
+        register_on_replace(&mut context, CAMERA, |context, entity| {
+            record_hook(context, "replace", entity)
+        });
+        register_on_despawn(&mut context, |context, entity| {
+            record_hook(context, "despawn", entity)
+        });
+
+        // Hooks only fire for components in their mask
+        let entity = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        add_components(&mut context, entity, NAME);
+        assert!(take_hook_calls().is_empty());
+
+        // On-add runs once the component is readable, and not for components already present
+        add_components(&mut context, entity, CAMERA);
+        add_components(&mut context, entity, CAMERA);
+        assert_eq!(take_hook_calls(), [("add", true)]);
+
+        // On-replace runs before overwriting, without add or remove hooks
+        let components = query_entity_components(&context, entity).unwrap();
+        set_entity_components(&mut context, entity, components);
+        assert_eq!(take_hook_calls(), [("replace", true)]);
+
+        // On-remove runs while the component is still readable
+        remove_components(&mut context, entity, CAMERA);
+        remove_components(&mut context, entity, CAMERA);
+        assert_eq!(take_hook_calls(), [("remove", true)]);
+
+        spawn_entities(&mut context, CAMERA, 1);
+        assert_eq!(take_hook_calls(), [("add", true)]);
+
+        // Despawn hooks run first, then on-remove for the entity's components
+        add_components(&mut context, entity, CAMERA);
+        take_hook_calls();
+        despawn_entities(&mut context, &[entity]);
+        assert_eq!(take_hook_calls(), [("despawn", true), ("remove", true)]);
+    }
+
+    #[test]
+    fn engine_hooks_complete_components() {
+        let mut context = Context::default();
+        register_on_add(&mut context, CAMERA, camera::on_camera_added);
+        register_on_add(&mut context, PARENT, transform::on_parent_added);
+        register_on_add(
+            &mut context,
+            GLOBAL_TRANSFORM,
+            transform::on_global_transform_added,
+        );
+
+        let camera = spawn_entities(&mut context, CAMERA, 1)[0];
+        assert_eq!(
+            component_mask(&context, camera),
+            Some(CAMERA | LOCAL_TRANSFORM)
+        );
+
+        add_components(&mut context, camera, PARENT);
+        assert_eq!(
+            component_mask(&context, camera),
+            Some(CAMERA | LOCAL_TRANSFORM | PARENT | GLOBAL_TRANSFORM | RENDER_TRANSFORM)
+        );
+    }
+
+    #[test]
+    fn query_filters() {
+        let mut context = Context::default();
+        let root = spawn_entities(&mut context, LOCAL_TRANSFORM, 1)[0];
+        let child = spawn_entities(&mut context, LOCAL_TRANSFORM | PARENT, 1)[0];
+        let lines = spawn_entities(&mut context, GLOBAL_TRANSFORM | LINES, 1)[0];
+        let quads = spawn_entities(&mut context, GLOBAL_TRANSFORM | QUADS | NAME, 1)[0];
+        spawn_entities(&mut context, NAME, 1);
+
+        let query = |context: &Context, filter| {
+            let mut entities = query_entities_filtered(context, filter);
+            entities.sort_by_key(|entity| entity.id);
+            entities
+        };
+        assert_eq!(
+            query(&context, QueryFilter::with(LOCAL_TRANSFORM)),
+            [root, child]
+        );
+        assert_eq!(query(&context, tree::ROOT_ENTITIES), [root]);
+        assert_eq!(
+            query(
+                &context,
+                QueryFilter::with(GLOBAL_TRANSFORM).any_of(LINES | QUADS)
+            ),
+            [lines, quads]
+        );
+        assert_eq!(
+            query(
+                &context,
+                QueryFilter::with(GLOBAL_TRANSFORM)
+                    .any_of(LINES | QUADS)
+                    .without(NAME)
+            ),
+            [lines]
+        );
+        assert!(query(&context, QueryFilter::with(LINES).without(GLOBAL_TRANSFORM)).is_empty());
+
+        // Despawned entities are left out
+        despawn_entities(&mut context, &[child]);
+        assert_eq!(query(&context, QueryFilter::with(LOCAL_TRANSFORM)), [root]);
+
+        let mut entity_refs = query_components(
+            &context,
+            QueryFilter::with(GLOBAL_TRANSFORM).any_of(LINES | QUADS),
+        );
+        entity_refs.sort_by_key(|entity_ref| entity_ref.entity.id);
+        let [lines_ref, quads_ref] = &entity_refs[..] else {
+            panic!("Expected two entities");
+        };
+        assert_eq!(lines_ref.entity, lines);
+        assert!(lines_ref.global_transform.is_some() && lines_ref.lines.is_some());
+        assert!(lines_ref.quads.is_none() && lines_ref.name.is_none());
+        assert_eq!(quads_ref.entity, quads);
+        assert!(quads_ref.quads.is_some() && quads_ref.name.is_some());
+        assert!(quads_ref.lines.is_none() && quads_ref.local_transform.is_none());
+    }
+}
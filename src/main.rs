@@ -11,11 +11,51 @@ use structopt::StructOpt;
 
 #[tokio::main]
 pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    let Options { command } = Options::from_args();
-    if matches!(command, Some(Command::Run) | None) {
-        let mut context = context::Context::default();
-        run::run(&mut context);
+    let Options {
+        command,
+        record,
+        capture,
+        capture_size: (capture_width, capture_height),
+        capture_rate,
+        capture_frames,
+    } = Options::from_args();
+    let mut context = context::Context::default();
+    context::console::initialize_logger(context.resources.log_sink.clone());
+    if let Some(path) = record {
+        context::replay::start_recording(&mut context, path);
+    }
+    let capture_settings = capture.map(|output| context::capture::CaptureSettings {
+        output,
+        width: capture_width,
+        height: capture_height,
+        frame_rate: capture_rate,
+        frame_limit: capture_frames,
+        ..Default::default()
+    });
+    match command.unwrap_or_default() {
+        Command::Run => {
+            if let Some(settings) = capture_settings {
+                context::capture::start_frame_capture(&mut context, settings, true);
+            }
+            run::run(&mut context)
+        }
+        Command::Replay { path, headless } => {
+            if !context::replay::start_replay(&mut context, &path) {
+                return Err(format!("Failed to load input recording {}", path.display()).into());
+            }
+            if let Some(settings) = capture_settings {
+                if headless {
+                    log::warn!("Frame capture needs a renderer and is ignored when headless");
+                } else {
+                    context::capture::start_frame_capture(&mut context, settings, true);
+                }
+            }
+            if headless {
+                run::run_headless(&mut context);
+            } else {
+                run::run(&mut context);
+            }
+        }
     }
     Ok(())
 }
@@ -1,6 +1,15 @@
-use crate::context::{camera, graphics, input, transform, ui, window, Context};
+use crate::context::{
+    actions, camera, camera_path, capture, config,
+    graphics::{self, RenderMode},
+    input, play, profiler, register_on_add, register_on_despawn, replay,
+    schedule::{self, has_renderer, in_render_mode, resource_changed, ResourceMask, Stage, System},
+    time, transform, tree, ui, update_events, window, Context, CAMERA, CAMERA_CONTROLLER,
+    CAMERA_PATH, GLOBAL_TRANSFORM, LOCAL_TRANSFORM, PARENT, RENDER_TRANSFORM,
+};
 
 pub fn run(context: &mut Context) {
+    add_component_hooks(context);
+    add_main_systems(context);
     let event_loop = match winit::event_loop::EventLoop::builder().build() {
         Ok(event_loop) => event_loop,
         Err(error) => {
@@ -31,6 +40,12 @@ impl winit::application::ApplicationHandler for Context {
         if self.resources.window.should_exit
             || matches!(event, winit::event::WindowEvent::CloseRequested)
         {
+            // A replay runs with the recording's config and bindings, which are not the user's
+            if !self.resources.replay.is_playing() {
+                config::save_editor_config_system(self);
+                actions::save_action_map_system(self);
+            }
+            replay::save_recording_system(self);
             event_loop.exit();
             return;
         }
@@ -39,6 +54,18 @@ impl winit::application::ApplicationHandler for Context {
             window_handle.request_redraw();
         }
     }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &winit::event_loop::ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        event: winit::event::DeviceEvent,
+    ) {
+        if self.resources.replay.is_playing() {
+            return;
+        }
+        input::receive_device_event(self, &event);
+    }
 }
 
 fn run_systems(context: &mut Context, event: &winit::event::WindowEvent) {
@@ -50,8 +77,11 @@ fn run_systems(context: &mut Context, event: &winit::event::WindowEvent) {
         // Systems than run when the window is resized
         winit::event::WindowEvent::Resized(physical_size) => {
             context.resources.window.physical_size = *physical_size;
-            let winit::dpi::PhysicalSize { width, height } = physical_size;
-            run_resize_systems(context, *width, *height);
+            let winit::dpi::PhysicalSize { width, height } = *physical_size;
+            context
+                .events
+                .window_resized
+                .send(window::WindowResized { width, height });
         }
         winit::event::WindowEvent::ScaleFactorChanged {
             scale_factor,
@@ -59,9 +89,14 @@ fn run_systems(context: &mut Context, event: &winit::event::WindowEvent) {
         } => {
             run_scale_factor_changed_systems(context, *scale_factor, inner_size_writer);
         }
+        // Live input is dropped while a recording replays, the recording supplies it instead
+        _ if context.resources.replay.is_playing() => {}
         event => {
             ui::receive_window_event(context, event);
             input::receive_window_event(context, event);
+            window::receive_window_event(context, event);
+            camera::receive_window_event(context, event);
+            actions::receive_window_event(context, event);
         }
     }
 }
@@ -71,31 +106,168 @@ fn run_initialization_systems(
     context: &mut Context,
     event_loop: &winit::event_loop::ActiveEventLoop,
 ) {
+    // A replay keeps the config and bindings it was recorded with
+    if !context.resources.replay.is_playing() {
+        config::load_editor_config_system(context);
+        actions::load_action_map_system(context);
+    }
     window::initialize_window_system(context, event_loop);
     graphics::initialize_graphics_system(context);
     ui::initialize_ui_system(context);
 }
 
+/// Registers the hooks that keep component invariants at mutation time
+fn add_component_hooks(context: &mut Context) {
+    register_on_add(context, CAMERA, camera::on_camera_added);
+    register_on_add(context, PARENT, transform::on_parent_added);
+    register_on_add(
+        context,
+        GLOBAL_TRANSFORM,
+        transform::on_global_transform_added,
+    );
+    register_on_despawn(context, tree::on_entity_despawned);
+}
+
+/// Registers the engine's per-frame systems.
+/// Applications add their own through `context.resources.schedule`.
+/// Systems that touch the window, gui or renderer are marked to stay on the main thread.
+fn add_main_systems(context: &mut Context) {
+    context.resources.schedule.add_systems([
+        System::new(window::update_frame_timing_system)
+            .in_stage(Stage::PreUpdate)
+            .writes_resource(ResourceMask::WINDOW),
+        System::new(graphics::resize_renderer_system)
+            .in_stage(Stage::PreUpdate)
+            .on_main_thread()
+            .run_if(has_renderer),
+        System::new(ui::ensure_tile_tree_system)
+            .in_stage(Stage::PreUpdate)
+            .on_main_thread(),
+        System::new(replay::replay_system)
+            .in_stage(Stage::PreUpdate)
+            .on_main_thread()
+            .after(window::update_frame_timing_system)
+            .writes_resource(ResourceMask::REPLAY)
+            .writes_resource(ResourceMask::INPUT)
+            .writes_resource(ResourceMask::WINDOW)
+            .writes_resource(ResourceMask::GRAPHICS),
+        System::new(capture::capture_timing_system)
+            .in_stage(Stage::PreUpdate)
+            .after(replay::replay_system)
+            .reads_resource(ResourceMask::FRAME_CAPTURE)
+            .writes_resource(ResourceMask::WINDOW),
+        System::new(input::escape_key_exit_system)
+            .in_stage(Stage::PreUpdate)
+            .after(replay::replay_system)
+            .reads_resource(ResourceMask::INPUT)
+            .reads_resource(ResourceMask::ACTION_MAP)
+            .writes_resource(ResourceMask::WINDOW)
+            .writes_resource(ResourceMask::GRAPHICS)
+            .run_if(in_render_mode(RenderMode::Run)),
+        System::new(window::cursor_grab_system)
+            .in_stage(Stage::PreUpdate)
+            .on_main_thread()
+            .after(input::escape_key_exit_system)
+            .reads_resource(ResourceMask::INPUT)
+            .reads_resource(ResourceMask::GRAPHICS)
+            .writes_resource(ResourceMask::WINDOW)
+            .run_if(|context: &Context| !context.resources.replay.is_playing()),
+        System::new(time::time_controls_system)
+            .in_stage(Stage::PreUpdate)
+            .after(replay::replay_system)
+            .reads_resource(ResourceMask::INPUT)
+            .reads_resource(ResourceMask::ACTION_MAP)
+            .writes_resource(ResourceMask::TIME)
+            .run_if(in_render_mode(RenderMode::Run)),
+        System::new(play::play_session_system)
+            .in_stage(Stage::PreUpdate)
+            .after(input::escape_key_exit_system)
+            .run_if(resource_changed(|context| {
+                context.resources.graphics.render_mode
+            })),
+        System::new(camera::camera_controller_system)
+            .in_stage(Stage::Update)
+            .reads_resource(ResourceMask::INPUT)
+            .reads_resource(ResourceMask::WINDOW)
+            .reads_resource(ResourceMask::ACTION_MAP)
+            .reads_resource(ResourceMask::ACTIVE_CAMERA_ENTITY)
+            .writes(CAMERA | CAMERA_CONTROLLER | LOCAL_TRANSFORM),
+        System::new(camera::fly_camera_system)
+            .in_stage(Stage::FixedUpdate)
+            .reads_resource(ResourceMask::INPUT)
+            .reads_resource(ResourceMask::ACTION_MAP)
+            .reads_resource(ResourceMask::TIME)
+            .reads_resource(ResourceMask::ACTIVE_CAMERA_ENTITY)
+            .writes(CAMERA_CONTROLLER | LOCAL_TRANSFORM),
+        System::new(camera_path::camera_path_playback_system)
+            .in_stage(Stage::Update)
+            .after(camera::camera_controller_system)
+            .reads_resource(ResourceMask::WINDOW)
+            .reads_resource(ResourceMask::ACTIVE_CAMERA_ENTITY)
+            .writes_resource(ResourceMask::SEQUENCER)
+            .reads(CAMERA_PATH | PARENT)
+            .writes(CAMERA | LOCAL_TRANSFORM)
+            .run_if(in_render_mode(RenderMode::Run)),
+        System::new(camera_path::update_camera_path_lines_system)
+            .in_stage(Stage::Update)
+            .reads_resource(ResourceMask::GRAPHICS)
+            .reads_resource(ResourceMask::SEQUENCER)
+            .writes(CAMERA_PATH),
+        System::new(transform::update_global_transforms_system)
+            .in_stage(Stage::PostUpdate)
+            .reads(LOCAL_TRANSFORM | PARENT)
+            .writes(GLOBAL_TRANSFORM),
+        System::new(time::interpolate_render_transforms_system)
+            .in_stage(Stage::PostUpdate)
+            .after(transform::update_global_transforms_system)
+            .reads(GLOBAL_TRANSFORM)
+            .writes(RENDER_TRANSFORM)
+            .writes_resource(ResourceMask::TIME),
+        System::new(graphics::render_frame_system)
+            .in_stage(Stage::Render)
+            .on_main_thread()
+            .run_if(has_renderer),
+        System::new(capture::capture_frame_system)
+            .in_stage(Stage::Render)
+            .on_main_thread()
+            .after(graphics::render_frame_system)
+            .run_if(has_renderer),
+        System::new(ui::create_ui_system)
+            .in_stage(Stage::Render)
+            .on_main_thread()
+            .before(graphics::render_frame_system)
+            .run_if(has_renderer),
+        System::new(input::reset_input_system)
+            .in_stage(Stage::Cleanup)
+            .reads_resource(ResourceMask::WINDOW)
+            .writes_resource(ResourceMask::INPUT),
+    ]);
+}
+
+/// Replays an input recording without a window or renderer, one frame per recorded frame
+pub fn run_headless(context: &mut Context) {
+    add_component_hooks(context);
+    add_main_systems(context);
+    while context.resources.replay.is_playing() {
+        run_frame(context);
+    }
+}
+
 // Systems that run every frame
 fn run_main_systems(context: &mut Context) {
-    // Wait for the renderer to be initialized before running systems
-    if context.resources.graphics.renderer.is_none() {
+    // Wait for the renderer to be initialized before running systems,
+    // systems that need it are guarded by a run condition for headless replays
+    if !has_renderer(context) {
         return;
     }
-    window::update_frame_timing_system(context);
-    ui::ensure_tile_tree_system(context);
-    input::escape_key_exit_system(context);
-    camera::look_camera_system(context);
-    camera::wasd_keyboard_controls_system(context);
-    transform::update_global_transforms_system(context);
-    ui::create_ui_system(context);
-    graphics::render_frame_system(context);
-    input::reset_input_system(context);
+    run_frame(context);
 }
 
-// Systems that run when the window is resized
-fn run_resize_systems(context: &mut Context, width: u32, height: u32) {
-    graphics::resize_renderer_system(context, width, height);
+fn run_frame(context: &mut Context) {
+    profiler::begin_frame_system(context);
+    schedule::run_schedule_system(context);
+    profiler::end_frame_system(context);
+    update_events(context);
 }
 
 // Systems that run when the window's scale factor changes